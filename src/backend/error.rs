@@ -0,0 +1,23 @@
+use thiserror::Error;
+
+use crate::{RespFrame, SimpleError};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum BackendError {
+    #[error("WRONGTYPE Operation against a key holding the wrong kind of value")]
+    WrongType,
+    #[error("ERR value is not an integer or out of range")]
+    NotInteger,
+    #[error("ERR value is not a valid float")]
+    NotFloat,
+    #[error("ERR increment or decrement would overflow")]
+    Overflow,
+    #[error("ERR increment would produce NaN or Infinity")]
+    NanOrInfinity,
+}
+
+impl From<BackendError> for RespFrame {
+    fn from(err: BackendError) -> Self {
+        SimpleError::new(err.to_string()).into()
+    }
+}
@@ -0,0 +1,218 @@
+use std::{
+    sync::atomic::Ordering,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use bytes::Bytes;
+use dashmap::mapref::entry::Entry;
+use tracing::debug;
+
+use super::Backend;
+
+// 和 redis 的 active expire cycle 一样：每次抽样一些设置了过期时间的 key，
+// 过期的超过 1/4 就继续抽样，直到用完这一轮的时间
+const ACTIVE_EXPIRE_INTERVAL: Duration = Duration::from_millis(100);
+const ACTIVE_EXPIRE_KEYS_PER_LOOP: usize = 20;
+const ACTIVE_EXPIRE_CYCLE_LIMIT: Duration = Duration::from_millis(25);
+
+// unix time in milliseconds
+pub fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+impl Backend {
+    // 设置过期时间（unix 毫秒），check 根据当前的过期时间决定是否设置，
+    // 时间已经过去的话直接删除 key。key 不存在或者 check 不通过返回 false
+    pub fn expire_at(&self, key: &Bytes, at: i64, check: impl FnOnce(Option<i64>) -> bool) -> bool {
+        self.expire_if_needed(key);
+        let Entry::Occupied(entry) = self.keyspace.entry(key.clone()) else {
+            return false;
+        };
+        let current = self.expires.get(key).map(|at| *at);
+        if !check(current) {
+            return false;
+        }
+        if at <= now_ms() {
+            self.expires.remove(key);
+            entry.remove();
+        } else {
+            self.set_expire(key.clone(), at);
+        }
+        true
+    }
+
+    // 设置过期时间都要通过这里，先写 expires 再加入 volatile_keys，
+    // 这样 forget_volatile 不会删掉刚设置了过期时间的 key
+    pub(crate) fn set_expire(&self, key: Bytes, at: i64) {
+        self.expires.insert(key.clone(), at);
+        self.volatile_keys.lock().unwrap().insert(key);
+    }
+
+    // None: key 不存在，Some(None): 没有过期时间
+    pub fn expire_time(&self, key: &[u8]) -> Option<Option<i64>> {
+        self.expire_if_needed(key);
+        let _guard = self.keyspace.get(key)?;
+        Some(self.expires.get(key).map(|at| *at))
+    }
+
+    pub fn persist(&self, key: &[u8]) -> bool {
+        self.expire_if_needed(key);
+        match self.keyspace.get(key) {
+            Some(_guard) => self.expires.remove(key).is_some(),
+            None => false,
+        }
+    }
+
+    // lazy expiration，访问 key 之前调用，返回 key 是否因为过期被删除
+    pub(crate) fn expire_if_needed(&self, key: &[u8]) -> bool {
+        let now = now_ms();
+        if self.expires.get(key).is_none_or(|at| *at > now) {
+            return false;
+        }
+        // 在 keyspace 的锁里再检查一次，过期时间可能已经被修改
+        self.keyspace
+            .remove_if(key, |_, _| {
+                self.expires.remove_if(key, |_, at| *at <= now).is_some()
+            })
+            .is_some()
+    }
+
+    // 执行一轮 active expire，返回删除的 key 的个数
+    pub fn active_expire_cycle(&self) -> usize {
+        let start = Instant::now();
+        let mut expired = 0;
+        loop {
+            let keys = self.sample_volatile_keys();
+            if keys.is_empty() {
+                break;
+            }
+            let now = now_ms();
+            let mut hits = 0;
+            for key in &keys {
+                // 先释放 expires 的锁，expire_if_needed 需要拿 keyspace 的锁
+                let at = self.expires.get(key).map(|at| *at);
+                match at {
+                    Some(at) if at <= now => {
+                        hits += 1;
+                        if self.expire_if_needed(key) {
+                            expired += 1;
+                        }
+                        self.forget_volatile(key);
+                    }
+                    Some(_) => {}
+                    None => self.forget_volatile(key),
+                }
+            }
+            if hits * 4 <= keys.len() || start.elapsed() > ACTIVE_EXPIRE_CYCLE_LIMIT {
+                break;
+            }
+        }
+        expired
+    }
+
+    // dashmap 不能随机取样，从 volatile_keys 中上次的位置继续取，
+    // 每次的开销只和取样的个数有关
+    fn sample_volatile_keys(&self) -> Vec<Bytes> {
+        let keys = self.volatile_keys.lock().unwrap();
+        let len = keys.len();
+        if len == 0 {
+            return Vec::new();
+        }
+        let start = self
+            .expire_cursor
+            .fetch_add(ACTIVE_EXPIRE_KEYS_PER_LOOP, Ordering::Relaxed);
+        (0..ACTIVE_EXPIRE_KEYS_PER_LOOP.min(len))
+            .filter_map(|i| keys.get_index((start + i) % len).cloned())
+            .collect()
+    }
+
+    // key 已经没有过期时间时从 volatile_keys 中删除，在锁里检查，
+    // 和 set_expire 不会互相覆盖
+    fn forget_volatile(&self, key: &Bytes) {
+        let mut keys = self.volatile_keys.lock().unwrap();
+        if !self.expires.contains_key(key) {
+            keys.swap_remove(key);
+        }
+    }
+
+    // 后台定期删除过期的 key，和 redis 一样每秒 10 次
+    pub async fn run_active_expire(self) {
+        let mut interval = tokio::time::interval(ACTIVE_EXPIRE_INTERVAL);
+        loop {
+            interval.tick().await;
+            let expired = self.active_expire_cycle();
+            if expired > 0 {
+                debug!("Active expire removed {expired} keys");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_lazy_expire() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("k".into(), "v".into());
+        assert!(backend.expire_at(&"k".into(), now_ms() + 10_000, |_| true));
+        assert!(backend.expire_time(b"k").unwrap().is_some());
+        assert!(backend.persist(b"k"));
+        assert_eq!(backend.expire_time(b"k"), Some(None));
+        assert!(!backend.persist(b"k"));
+
+        // 时间已经过去，直接删除
+        assert!(backend.expire_at(&"k".into(), now_ms() - 1, |_| true));
+        assert_eq!(backend.get(b"k")?, None);
+        assert_eq!(backend.expire_time(b"k"), None);
+        assert!(!backend.expire_at(&"k".into(), now_ms() + 10_000, |_| true));
+
+        backend.set("k".into(), "v".into());
+        backend.expires.insert("k".into(), now_ms() - 1);
+        assert_eq!(backend.get(b"k")?, None);
+        assert!(backend.keyspace.is_empty());
+        assert!(backend.expires.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_clears_expire() {
+        let backend = Backend::new();
+        backend.set("k".into(), "v".into());
+        assert!(backend.expire_at(&"k".into(), now_ms() + 10_000, |_| true));
+        backend.set("k".into(), "v2".into());
+        assert_eq!(backend.expire_time(b"k"), Some(None));
+    }
+
+    #[test]
+    fn test_active_expire_cycle() {
+        let backend = Backend::new();
+        for i in 0..100 {
+            let key = Bytes::from(format!("k{i}"));
+            backend.set(key.clone(), "v".into());
+            let at = if i % 2 == 0 {
+                now_ms() - 1
+            } else {
+                now_ms() + 10_000
+            };
+            backend.set_expire(key, at);
+        }
+        // 已经没有过期时间的 key 在取样时从 volatile_keys 中删除
+        backend.set("k1".into(), "v".into());
+        assert_eq!(backend.volatile_keys.lock().unwrap().len(), 100);
+        // 每轮只抽样一部分 key，多执行几轮
+        let mut expired = 0;
+        for _ in 0..100 {
+            expired += backend.active_expire_cycle();
+        }
+        assert_eq!(expired, 50);
+        assert_eq!(backend.keyspace.len(), 50);
+        assert_eq!(backend.expires.len(), 49);
+        assert_eq!(backend.volatile_keys.lock().unwrap().len(), 49);
+    }
+}
@@ -1,16 +1,63 @@
-use std::{ops::Deref, sync::Arc};
+mod error;
+mod expire;
+mod value;
 
-use dashmap::DashMap;
+use std::{
+    collections::HashMap,
+    ops::Deref,
+    sync::{atomic::AtomicUsize, Arc, Mutex},
+};
 
-use crate::RespFrame;
+use bytes::Bytes;
+use dashmap::{mapref::entry::Entry, DashMap};
+use indexmap::IndexSet;
 
-type Map = DashMap<String, RespFrame>;
-type HMap = DashMap<String, Map>;
+pub use error::BackendError;
+pub use expire::now_ms;
+pub use value::Value;
+pub(crate) use value::{format_float, parse_float, parse_int};
 
+pub type BackendResult<T> = Result<T, BackendError>;
+
+// SET 的选项
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SetOptions {
+    pub condition: Option<SetCondition>,
+    // None: 清除原来的过期时间
+    pub ttl: Option<SetTtl>,
+    // 返回旧的值，旧的值不是 string 时报错
+    pub get: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SetCondition {
+    // key 不存在时才写入
+    Nx,
+    // key 存在时才写入
+    Xx,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SetTtl {
+    // unix time in milliseconds
+    At(i64),
+    // KEEPTTL
+    Keep,
+}
+
+// 所有类型的 key 在同一个 keyspace 里，同一个 key 只能有一种类型。
+// 和 redis 一样，设置了过期时间的 key 另外记录在 expires 中，
+// 修改 expires 时要先拿到 keyspace 中这个 key 的锁
 #[derive(Default)]
 pub struct BackendState {
-    pub(crate) map: Map,
-    pub(crate) hmap: HMap,
+    pub(crate) keyspace: DashMap<Bytes, Value>,
+    // key -> unix time in milliseconds
+    pub(crate) expires: DashMap<Bytes, i64>,
+    // keys sampled by active expire, may contain keys without expire time,
+    // they are removed when sampled. IndexSet 可以按下标取样
+    volatile_keys: Mutex<IndexSet<Bytes>>,
+    // where the next active expire cycle starts sampling
+    expire_cursor: AtomicUsize,
 }
 
 #[derive(Clone)]
@@ -22,23 +69,172 @@ impl Backend {
         Backend(Arc::new(s))
     }
 
-    pub fn get(&self, key: &str) -> Option<RespFrame> {
-        self.map.get(key).map(|v| v.value().clone())
+    pub fn get(&self, key: &[u8]) -> BackendResult<Option<Bytes>> {
+        self.expire_if_needed(key);
+        match self.keyspace.get(key).as_deref() {
+            Some(Value::String(value)) => Ok(Some(value.clone())),
+            Some(_) => Err(BackendError::WrongType),
+            None => Ok(None),
+        }
+    }
+
+    // SET 会覆盖任何类型的旧值，并清除过期时间
+    pub fn set(&self, key: Bytes, value: Bytes) {
+        let entry = self.keyspace.entry(key.clone());
+        self.expires.remove(&key);
+        entry.insert(Value::String(value));
+    }
+
+    // 返回是否写入以及旧的 string 值
+    pub fn set_with(
+        &self,
+        key: Bytes,
+        value: Bytes,
+        options: &SetOptions,
+    ) -> BackendResult<(bool, Option<Bytes>)> {
+        self.expire_if_needed(&key);
+        let entry = self.keyspace.entry(key.clone());
+        let old = match &entry {
+            Entry::Occupied(e) => match e.get() {
+                Value::String(old) => Some(old.clone()),
+                _ if options.get => return Err(BackendError::WrongType),
+                _ => None,
+            },
+            Entry::Vacant(_) => None,
+        };
+        let exists = matches!(entry, Entry::Occupied(_));
+        let write = match options.condition {
+            Some(SetCondition::Nx) => !exists,
+            Some(SetCondition::Xx) => exists,
+            None => true,
+        };
+        if !write {
+            return Ok((false, old));
+        }
+        match options.ttl {
+            Some(SetTtl::At(at)) => self.set_expire(key, at),
+            Some(SetTtl::Keep) => {}
+            None => {
+                self.expires.remove(&key);
+            }
+        }
+        entry.insert(Value::String(value));
+        Ok((true, old))
+    }
+
+    // INCR/DECR 等，在 key 的锁里完成读取和写入，保留过期时间
+    pub fn incr_by(&self, key: Bytes, delta: i64) -> BackendResult<i64> {
+        self.update_string(key, |old| {
+            let n = match old {
+                Some(old) => parse_int(old).ok_or(BackendError::NotInteger)?,
+                None => 0,
+            };
+            let n = n.checked_add(delta).ok_or(BackendError::Overflow)?;
+            Ok((n.to_string().into(), n))
+        })
+    }
+
+    // 返回保存的字符串，回复和保存的值一致
+    pub fn incr_by_float(&self, key: Bytes, delta: f64) -> BackendResult<Bytes> {
+        self.update_string(key, |old| {
+            let f = match old {
+                Some(old) => parse_float(old).ok_or(BackendError::NotFloat)?,
+                None => 0.0,
+            };
+            let f = f + delta;
+            if !f.is_finite() {
+                return Err(BackendError::NanOrInfinity);
+            }
+            let value = Bytes::from(format_float(f));
+            Ok((value.clone(), value))
+        })
+    }
+
+    // f 根据旧的 string 值计算新的值，key 不存在时旧的值是 None
+    fn update_string<T>(
+        &self,
+        key: Bytes,
+        f: impl FnOnce(Option<&Bytes>) -> BackendResult<(Bytes, T)>,
+    ) -> BackendResult<T> {
+        self.expire_if_needed(&key);
+        match self.keyspace.entry(key) {
+            Entry::Occupied(mut entry) => match entry.get_mut() {
+                Value::String(value) => {
+                    let (new, ret) = f(Some(value))?;
+                    *value = new;
+                    Ok(ret)
+                }
+                _ => Err(BackendError::WrongType),
+            },
+            Entry::Vacant(entry) => {
+                let (new, ret) = f(None)?;
+                entry.insert(Value::String(new));
+                Ok(ret)
+            }
+        }
     }
 
-    pub fn set(&self, key: String, value: RespFrame) {
-        self.map.insert(key, value);
+    pub fn hget(&self, key: &[u8], field: &[u8]) -> BackendResult<Option<Bytes>> {
+        self.expire_if_needed(key);
+        match self.keyspace.get(key).as_deref() {
+            Some(Value::Hash(hash)) => Ok(hash.get(field).cloned()),
+            Some(_) => Err(BackendError::WrongType),
+            None => Ok(None),
+        }
     }
 
-    pub fn hget(&self, key: &str, field: &str) -> Option<RespFrame> {
-        self.hmap
-            .get(key)
-            .and_then(|map| map.get(field).map(|v| v.value().clone()))
+    // 在 key 的锁里写入所有的 field，返回新增的 field 的个数
+    pub fn hset(
+        &self,
+        key: Bytes,
+        fields: impl IntoIterator<Item = (Bytes, Bytes)>,
+    ) -> BackendResult<usize> {
+        self.expire_if_needed(&key);
+        match self.keyspace.entry(key) {
+            Entry::Occupied(mut entry) => match entry.get_mut() {
+                Value::Hash(hash) => Ok(fields
+                    .into_iter()
+                    .map(|(field, value)| hash.insert(field, value))
+                    .filter(Option::is_none)
+                    .count()),
+                _ => Err(BackendError::WrongType),
+            },
+            Entry::Vacant(entry) => {
+                let hash = HashMap::from_iter(fields);
+                let added = hash.len();
+                if added > 0 {
+                    entry.insert(Value::Hash(hash));
+                }
+                Ok(added)
+            }
+        }
     }
 
-    pub fn hset(&self, key: String, field: String, value: RespFrame) {
-        let map = self.hmap.entry(key).or_default();
-        map.insert(field, value);
+    // 不存在的 key 返回空的列表
+    pub fn hgetall(&self, key: &[u8]) -> BackendResult<Vec<(Bytes, Bytes)>> {
+        self.expire_if_needed(key);
+        match self.keyspace.get(key).as_deref() {
+            Some(Value::Hash(hash)) => {
+                Ok(hash.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+            }
+            Some(_) => Err(BackendError::WrongType),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub fn del(&self, key: &[u8]) -> bool {
+        self.expire_if_needed(key);
+        self.keyspace
+            .remove_if(key, |_, _| {
+                self.expires.remove(key);
+                true
+            })
+            .is_some()
+    }
+
+    pub fn key_type(&self, key: &[u8]) -> Option<&'static str> {
+        self.expire_if_needed(key);
+        self.keyspace.get(key).map(|v| v.type_name())
     }
 }
 
@@ -55,3 +251,95 @@ impl Deref for Backend {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_keyspace_types() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("s".into(), "v".into());
+        backend.hset("h".into(), [("f".into(), "v".into())])?;
+        assert_eq!(backend.key_type(b"s"), Some("string"));
+        assert_eq!(backend.key_type(b"h"), Some("hash"));
+        assert_eq!(backend.key_type(b"none"), None);
+
+        assert_eq!(backend.get(b"h"), Err(BackendError::WrongType));
+        assert_eq!(backend.hget(b"s", b"f"), Err(BackendError::WrongType));
+        assert_eq!(
+            backend.hset("s".into(), [("f".into(), "1".into())]),
+            Err(BackendError::WrongType)
+        );
+        assert_eq!(backend.hgetall(b"none")?, vec![]);
+
+        // SET 覆盖 hash
+        backend.set("h".into(), "1".into());
+        assert_eq!(backend.get(b"h")?, Some("1".into()));
+        assert!(backend.del(b"h"));
+        assert!(!backend.del(b"h"));
+        assert_eq!(backend.get(b"h")?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_binary_keys() -> Result<()> {
+        let backend = Backend::new();
+        let key = Bytes::from_static(b"k\xff\x00");
+        backend.set(key.clone(), Bytes::from_static(b"\x80\r\n"));
+        assert_eq!(backend.get(&key)?, Some(Bytes::from_static(b"\x80\r\n")));
+        // lossy 转换后的 key 不应该能读到
+        assert_eq!(backend.get("k\u{fffd}\0".as_bytes())?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_with_options() -> Result<()> {
+        let backend = Backend::new();
+        let nx = SetOptions {
+            condition: Some(SetCondition::Nx),
+            get: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            backend.set_with("k".into(), "v1".into(), &nx)?,
+            (true, None)
+        );
+        assert_eq!(
+            backend.set_with("k".into(), "v2".into(), &nx)?,
+            (false, Some("v1".into()))
+        );
+
+        let xx = SetOptions {
+            condition: Some(SetCondition::Xx),
+            ttl: Some(SetTtl::At(now_ms() + 10_000)),
+            ..Default::default()
+        };
+        assert_eq!(
+            backend.set_with("none".into(), "v".into(), &xx)?,
+            (false, None)
+        );
+        assert_eq!(
+            backend.set_with("k".into(), "v2".into(), &xx)?,
+            (true, Some("v1".into()))
+        );
+        assert!(backend.expire_time(b"k").unwrap().is_some());
+
+        let keep = SetOptions {
+            ttl: Some(SetTtl::Keep),
+            ..Default::default()
+        };
+        backend.set_with("k".into(), "v3".into(), &keep)?;
+        assert!(backend.expire_time(b"k").unwrap().is_some());
+        backend.set_with("k".into(), "v4".into(), &SetOptions::default())?;
+        assert_eq!(backend.expire_time(b"k"), Some(None));
+
+        backend.hset("h".into(), [("f".into(), "v".into())])?;
+        assert_eq!(
+            backend.set_with("h".into(), "v".into(), &nx),
+            Err(BackendError::WrongType)
+        );
+        Ok(())
+    }
+}
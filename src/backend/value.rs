@@ -0,0 +1,115 @@
+use std::{collections::HashMap, str::from_utf8};
+
+use bytes::Bytes;
+
+use crate::RespDouble;
+
+// keyspace 中保存的值，都是二进制安全的字节，以后再加 List、Set、ZSet 等类型
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(Bytes),
+    Hash(HashMap<Bytes, Bytes>),
+}
+
+impl Value {
+    // TYPE 命令的回复
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::Hash(_) => "hash",
+        }
+    }
+}
+
+// 和 redis 的 string2ll 一样严格：不接受 '+'、前导 0 和空白
+pub(crate) fn parse_int(s: &[u8]) -> Option<i64> {
+    let digits = s.strip_prefix(b"-").unwrap_or(s);
+    if digits.is_empty() || (digits[0] == b'0' && s.len() > 1) || !digits[0].is_ascii_digit() {
+        return None;
+    }
+    from_utf8(s).ok()?.parse().ok()
+}
+
+// inf 可以，NaN 不行
+pub(crate) fn parse_float(s: &[u8]) -> Option<f64> {
+    let f = from_utf8(s).ok()?.parse::<RespDouble>().ok()?;
+    (!f.is_nan()).then_some(f.value())
+}
+
+// redis 用 long double 和 %.17Lg 格式化 INCRBYFLOAT 的结果，
+// f64 只有 15 位有效数字是可靠的，用 %.15g 才能让 0.1 + 0.2 得到 0.3
+const FLOAT_DIGITS: usize = 15;
+
+// 和 C 的 %g 一样选择定点或者科学计数法，然后去掉末尾的 0
+pub(crate) fn format_float(f: f64) -> String {
+    if !f.is_finite() {
+        return RespDouble::new(f).to_string();
+    }
+    let sci = format!("{:.*e}", FLOAT_DIGITS - 1, f);
+    let (mantissa, exp) = sci.split_once('e').unwrap_or((&sci, "0"));
+    let exp: i32 = exp.parse().unwrap_or_default();
+    if exp < -4 || exp >= FLOAT_DIGITS as i32 {
+        let sign = if exp < 0 { '-' } else { '+' };
+        format!("{}e{sign}{:02}", trim_zeros(mantissa), exp.abs())
+    } else {
+        let fixed = format!("{:.*}", (FLOAT_DIGITS as i32 - 1 - exp) as usize, f);
+        trim_zeros(&fixed).to_string()
+    }
+}
+
+fn trim_zeros(s: &str) -> &str {
+    match s.contains('.') {
+        true => s.trim_end_matches('0').trim_end_matches('.'),
+        false => s,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_int() {
+        assert_eq!(parse_int(b"0"), Some(0));
+        assert_eq!(parse_int(b"-42"), Some(-42));
+        assert_eq!(parse_int(b"9223372036854775807"), Some(i64::MAX));
+        assert_eq!(parse_int(b"-9223372036854775808"), Some(i64::MIN));
+        for s in [
+            &b""[..],
+            b"-",
+            b"+1",
+            b"01",
+            b"-0",
+            b" 1",
+            b"1 ",
+            b"1.0",
+            b"9223372036854775808",
+        ] {
+            assert_eq!(parse_int(s), None);
+        }
+    }
+
+    #[test]
+    fn test_parse_float() {
+        assert_eq!(parse_float(b"10.5"), Some(10.5));
+        assert_eq!(parse_float(b"5.0e3"), Some(5000.0));
+        assert_eq!(parse_float(b"-inf"), Some(f64::NEG_INFINITY));
+        assert_eq!(parse_float(b"nan"), None);
+        assert_eq!(parse_float(b"abc"), None);
+        assert_eq!(parse_float(b" 1"), None);
+    }
+
+    #[test]
+    fn test_format_float() {
+        assert_eq!(format_float(0.2 + 0.1), "0.3");
+        assert_eq!(format_float(10.5 + 0.1), "10.6");
+        assert_eq!(format_float(5200.0), "5200");
+        assert_eq!(format_float(-1.5), "-1.5");
+        assert_eq!(format_float(0.0), "0");
+        assert_eq!(format_float(0.0001), "0.0001");
+        assert_eq!(format_float(0.00001), "1e-05");
+        assert_eq!(format_float(1e20), "1e+20");
+        assert_eq!(format_float(123456789012345.0), "123456789012345");
+        assert_eq!(format_float(999999999999999.9), "1e+15");
+    }
+}
@@ -0,0 +1,135 @@
+use super::{CommandError, CommandReply};
+use crate::{
+    Backend, BulkString, CommandExecutor, Hello, RespArray, RespFrame, RespMap, RespProtocol,
+    SimpleError,
+};
+
+impl CommandExecutor for Hello {
+    // 不知道连接当前的协议，按默认的协议处理，网络层使用 Command::execute_on
+    fn execute(&self, _backend: &Backend) -> RespFrame {
+        self.negotiate(RespProtocol::default()).frame
+    }
+}
+
+impl Hello {
+    // 没有指定版本时回复当前的版本，版本不支持时回复错误，不切换协议
+    pub fn negotiate(&self, current: RespProtocol) -> CommandReply {
+        let version = self.protover.unwrap_or(current.version());
+        let Some(protocol) = RespProtocol::from_version(version) else {
+            return CommandReply {
+                frame: SimpleError::new("NOPROTO unsupported protocol version").into(),
+                protocol: current,
+            };
+        };
+        let mut info = RespMap::new();
+        let mut insert = |k: &str, v: RespFrame| info.insert(BulkString::from(k).into(), v);
+        insert("server", BulkString::from("redis").into());
+        insert(
+            "version",
+            BulkString::from(env!("CARGO_PKG_VERSION")).into(),
+        );
+        insert("proto", version.into());
+        insert("mode", BulkString::from("standalone").into());
+        insert("role", BulkString::from("master").into());
+        insert("modules", RespArray::new([]).into());
+        CommandReply {
+            frame: info.into(),
+            protocol,
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Hello {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let protover = match value.get(1) {
+            Some(RespFrame::BulkString(v)) => Some(
+                std::str::from_utf8(v)
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or_else(|| {
+                        CommandError::InvalidArguments(
+                            "Protocol version is not an integer or out of range".to_string(),
+                        )
+                    })?,
+            ),
+            Some(_) => {
+                return Err(CommandError::InvalidArguments(
+                    "Protocol version must be a bulk string".to_string(),
+                ))
+            }
+            None => None,
+        };
+        // AUTH 和 SETNAME 选项还不支持
+        if let Some(option) = value.get(2) {
+            let option = match option {
+                RespFrame::BulkString(option) => option.to_string(),
+                _ => String::new(),
+            };
+            return Err(CommandError::InvalidArguments(format!(
+                "Syntax error in HELLO option '{option}'"
+            )));
+        }
+        Ok(Self { protover })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RespEncode;
+    use anyhow::Result;
+
+    #[test]
+    fn test_hello_command() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = Hello::try_from(RespArray::new([
+            BulkString::from("HELLO").into(),
+            BulkString::from("3").into(),
+        ]))?;
+        assert_eq!(cmd, Hello { protover: Some(3) });
+        let RespFrame::Map(info) = cmd.execute(&backend) else {
+            panic!("HELLO must reply a map");
+        };
+        assert_eq!(
+            info.get(&RespFrame::from(BulkString::from("proto"))),
+            Some(&RespFrame::Integer(3))
+        );
+
+        let cmd = Hello { protover: Some(4) };
+        assert_eq!(
+            cmd.execute(&backend).encode(),
+            b"-NOPROTO unsupported protocol version\r\n"
+        );
+
+        let cmd = Hello::try_from(RespArray::new([
+            BulkString::from("HELLO").into(),
+            BulkString::from("x").into(),
+        ]));
+        assert!(cmd.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hello_negotiate() {
+        let reply = Hello { protover: Some(3) }.negotiate(RespProtocol::Resp2);
+        assert_eq!(reply.protocol, RespProtocol::Resp3);
+
+        // 没有指定版本时保持当前的协议
+        let reply = Hello { protover: None }.negotiate(RespProtocol::Resp3);
+        assert_eq!(reply.protocol, RespProtocol::Resp3);
+        let RespFrame::Map(info) = reply.frame else {
+            panic!("HELLO must reply a map");
+        };
+        assert_eq!(
+            info.get(&RespFrame::from(BulkString::from("proto"))),
+            Some(&RespFrame::Integer(3))
+        );
+
+        // 版本不支持时不切换
+        let reply = Hello { protover: Some(4) }.negotiate(RespProtocol::Resp3);
+        assert_eq!(reply.protocol, RespProtocol::Resp3);
+        assert!(matches!(reply.frame, RespFrame::SimpleError(_)));
+    }
+}
@@ -0,0 +1,157 @@
+use super::{bulk_arg, float_arg, int_arg, CommandError};
+use crate::{Backend, BulkString, CommandExecutor, IncrBy, IncrByFloat, RespArray, RespFrame};
+
+impl CommandExecutor for IncrBy {
+    fn execute(&self, backend: &Backend) -> RespFrame {
+        match backend.incr_by(self.key.clone(), self.delta) {
+            Ok(n) => RespFrame::Integer(n),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for IncrByFloat {
+    fn execute(&self, backend: &Backend) -> RespFrame {
+        // 和 redis 一样回复 bulk string
+        match backend.incr_by_float(self.key.clone(), self.delta) {
+            Ok(value) => BulkString::new(value).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for IncrBy {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key: bulk_arg(&value, 1)?,
+            delta: int_arg(&value, 2)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for IncrByFloat {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key: bulk_arg(&value, 1)?,
+            delta: float_arg(&value, 2)?,
+        })
+    }
+}
+
+// INCR/DECR
+pub(super) fn parse_incr(value: RespArray, delta: i64) -> Result<IncrBy, CommandError> {
+    Ok(IncrBy {
+        key: bulk_arg(&value, 1)?,
+        delta,
+    })
+}
+
+pub(super) fn parse_decrby(value: RespArray) -> Result<IncrBy, CommandError> {
+    let cmd = IncrBy::try_from(value)?;
+    let delta = cmd
+        .delta
+        .checked_neg()
+        .ok_or_else(|| CommandError::InvalidArguments("decrement would overflow".to_string()))?;
+    Ok(IncrBy { delta, ..cmd })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cmd::test_utils::{error, run},
+        now_ms, RespEncode,
+    };
+    use anyhow::Result;
+
+    #[test]
+    fn test_incr_decr_commands() -> Result<()> {
+        let backend = Backend::new();
+        assert_eq!(run(&backend, &["incr", "n"])?, RespFrame::Integer(1));
+        assert_eq!(
+            run(&backend, &["incrby", "n", "10"])?,
+            RespFrame::Integer(11)
+        );
+        assert_eq!(run(&backend, &["decr", "n"])?, RespFrame::Integer(10));
+        assert_eq!(
+            run(&backend, &["decrby", "n", "-5"])?,
+            RespFrame::Integer(15)
+        );
+        assert_eq!(backend.get(b"n")?, Some("15".into()));
+
+        // 保留过期时间
+        backend.expire_at(&"n".into(), now_ms() + 10_000, |_| true);
+        run(&backend, &["incr", "n"])?;
+        assert!(backend.expire_time(b"n").unwrap().is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_incr_errors() -> Result<()> {
+        let backend = Backend::new();
+        let not_integer = b"-ERR value is not an integer or out of range\r\n";
+        backend.set("s".into(), "abc".into());
+        assert_eq!(run(&backend, &["incr", "s"])?.encode(), not_integer);
+        backend.set("s".into(), " 1".into());
+        assert_eq!(run(&backend, &["incr", "s"])?.encode(), not_integer);
+        assert!(run(&backend, &["incrby", "n", "1.5"]).is_err());
+
+        backend.set("max".into(), i64::MAX.to_string().into());
+        assert_eq!(
+            run(&backend, &["incr", "max"])?.encode(),
+            b"-ERR increment or decrement would overflow\r\n"
+        );
+        assert_eq!(backend.get(b"max")?, Some(i64::MAX.to_string().into()));
+        assert_eq!(
+            error(&["decrby", "n", "-9223372036854775808"]),
+            b"-ERR decrement would overflow\r\n"
+        );
+
+        backend.hset("h".into(), [("f".into(), "1".into())])?;
+        assert_eq!(
+            run(&backend, &["incr", "h"])?.encode(),
+            b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_incrbyfloat_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("f".into(), "10.50".into());
+        assert_eq!(
+            run(&backend, &["incrbyfloat", "f", "0.1"])?,
+            BulkString::from("10.6").into()
+        );
+        assert_eq!(
+            run(&backend, &["incrbyfloat", "f", "-5"])?,
+            BulkString::from("5.6").into()
+        );
+        backend.set("f".into(), "5.0e3".into());
+        assert_eq!(
+            run(&backend, &["incrbyfloat", "f", "2.0e2"])?,
+            BulkString::from("5200").into()
+        );
+        backend.set("f".into(), "0.2".into());
+        assert_eq!(
+            run(&backend, &["incrbyfloat", "f", "0.1"])?,
+            BulkString::from("0.3").into()
+        );
+        assert_eq!(backend.get(b"f")?, Some("0.3".into()));
+        assert_eq!(
+            run(&backend, &["incrbyfloat", "f", "inf"])?.encode(),
+            b"-ERR increment would produce NaN or Infinity\r\n"
+        );
+        assert!(run(&backend, &["incrbyfloat", "f", "abc"]).is_err());
+        backend.set("s".into(), "abc".into());
+        assert_eq!(
+            run(&backend, &["incrbyfloat", "s", "1"])?.encode(),
+            b"-ERR value is not a valid float\r\n"
+        );
+        Ok(())
+    }
+}
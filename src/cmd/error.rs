@@ -1,13 +1,25 @@
 use thiserror::Error;
 
+use crate::{RespFrame, SimpleError};
+
+// Display 就是回复给客户端的错误信息（不含 ERR 前缀）
 #[derive(Debug, Error, PartialEq)]
 pub enum CommandError {
-    #[error("Invalid command: {0}")]
+    #[error("{0}")]
     InvalidCommand(String),
-    #[error("Invalid command arguments: {0}")]
+    #[error("{0}")]
     InvalidArguments(String),
+    #[error("wrong number of arguments for '{0}' command")]
+    WrongArity(&'static str),
     #[error("Invalid command length: {0}")]
     InvalidLength(isize),
     #[error("Command is not complete")]
     NotComplete,
 }
+
+// 命令错误只回复错误，不会关闭连接
+impl From<CommandError> for RespFrame {
+    fn from(err: CommandError) -> Self {
+        SimpleError::new(format!("ERR {err}")).into()
+    }
+}
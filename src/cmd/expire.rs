@@ -0,0 +1,265 @@
+use super::{bulk_arg, int_arg, CommandError};
+use crate::{
+    now_ms, Backend, CommandExecutor, Expire, ExpireCondition, ExpireTime, Persist, RespArray,
+    RespFrame, Ttl,
+};
+
+impl CommandExecutor for Expire {
+    fn execute(&self, backend: &Backend) -> RespFrame {
+        let updated = backend.expire_at(&self.key, self.at, |current| {
+            self.conditions.iter().all(|c| c.check(current, self.at))
+        });
+        RespFrame::Integer(updated as i64)
+    }
+}
+
+impl CommandExecutor for Ttl {
+    fn execute(&self, backend: &Backend) -> RespFrame {
+        let ttl = match backend.expire_time(&self.key) {
+            None => -2,
+            Some(None) => -1,
+            Some(Some(at)) => {
+                let ttl = (at - now_ms()).max(0);
+                if self.millis {
+                    ttl
+                } else {
+                    (ttl + 500) / 1000
+                }
+            }
+        };
+        RespFrame::Integer(ttl)
+    }
+}
+
+impl CommandExecutor for ExpireTime {
+    fn execute(&self, backend: &Backend) -> RespFrame {
+        let at = match backend.expire_time(&self.key) {
+            None => -2,
+            Some(None) => -1,
+            Some(Some(at)) => (at + 500) / 1000,
+        };
+        RespFrame::Integer(at)
+    }
+}
+
+impl CommandExecutor for Persist {
+    fn execute(&self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.persist(&self.key) as i64)
+    }
+}
+
+impl ExpireCondition {
+    // current: 当前的过期时间，at: 新的过期时间
+    fn check(&self, current: Option<i64>, at: i64) -> bool {
+        match self {
+            ExpireCondition::Nx => current.is_none(),
+            ExpireCondition::Xx => current.is_some(),
+            ExpireCondition::Gt => current.is_some_and(|current| at > current),
+            ExpireCondition::Lt => current.is_none_or(|current| at < current),
+        }
+    }
+}
+
+// unit_ms: 参数的单位是多少毫秒，absolute: 参数是 unix 时间还是相对时间
+pub(super) fn parse_expire(
+    value: RespArray,
+    name: &'static str,
+    unit_ms: i64,
+    absolute: bool,
+) -> Result<Expire, CommandError> {
+    let key = bulk_arg(&value, 1)?;
+    let when = int_arg(&value, 2)?;
+    let mut conditions = Vec::new();
+    for i in 3..value.len() {
+        let option = bulk_arg(&value, i)?;
+        let condition = match option.to_ascii_uppercase().as_slice() {
+            b"NX" => ExpireCondition::Nx,
+            b"XX" => ExpireCondition::Xx,
+            b"GT" => ExpireCondition::Gt,
+            b"LT" => ExpireCondition::Lt,
+            _ => {
+                return Err(CommandError::InvalidArguments(format!(
+                    "Unsupported option {}",
+                    String::from_utf8_lossy(&option)
+                )))
+            }
+        };
+        conditions.push(condition);
+    }
+    let has = |c| conditions.contains(&c);
+    if has(ExpireCondition::Nx)
+        && (has(ExpireCondition::Xx) || has(ExpireCondition::Gt) || has(ExpireCondition::Lt))
+    {
+        return Err(CommandError::InvalidArguments(
+            "NX and XX, GT or LT options at the same time are not compatible".to_string(),
+        ));
+    }
+    if has(ExpireCondition::Gt) && has(ExpireCondition::Lt) {
+        return Err(CommandError::InvalidArguments(
+            "GT and LT options at the same time are not compatible".to_string(),
+        ));
+    }
+
+    Ok(Expire {
+        key,
+        at: deadline(when, name, unit_ms, absolute)?,
+        conditions,
+    })
+}
+
+// 把命令参数中的时间转换成 unix 毫秒
+pub(super) fn deadline(
+    when: i64,
+    name: &'static str,
+    unit_ms: i64,
+    absolute: bool,
+) -> Result<i64, CommandError> {
+    let base = if absolute { 0 } else { now_ms() };
+    when.checked_mul(unit_ms)
+        .and_then(|when| when.checked_add(base))
+        .ok_or_else(|| {
+            CommandError::InvalidArguments(format!("invalid expire time in '{name}' command"))
+        })
+}
+
+impl TryFrom<RespArray> for Ttl {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key: bulk_arg(&value, 1)?,
+            millis: false,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ExpireTime {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key: bulk_arg(&value, 1)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Persist {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key: bulk_arg(&value, 1)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::test_utils::{error, run};
+    use anyhow::Result;
+
+    #[test]
+    fn test_expire_ttl_commands() -> Result<()> {
+        let backend = Backend::new();
+        assert_eq!(run(&backend, &["ttl", "k"])?, RespFrame::Integer(-2));
+        assert_eq!(
+            run(&backend, &["expire", "k", "100"])?,
+            RespFrame::Integer(0)
+        );
+
+        backend.set("k".into(), "v".into());
+        assert_eq!(run(&backend, &["ttl", "k"])?, RespFrame::Integer(-1));
+        assert_eq!(run(&backend, &["expiretime", "k"])?, RespFrame::Integer(-1));
+        assert_eq!(
+            run(&backend, &["expire", "k", "100"])?,
+            RespFrame::Integer(1)
+        );
+        assert_eq!(run(&backend, &["ttl", "k"])?, RespFrame::Integer(100));
+        let RespFrame::Integer(pttl) = run(&backend, &["pttl", "k"])? else {
+            panic!("PTTL must reply an integer");
+        };
+        assert!(pttl > 99_000 && pttl <= 100_000);
+        let expected = (now_ms() + 100_000 + 500) / 1000;
+        let RespFrame::Integer(at) = run(&backend, &["expiretime", "k"])? else {
+            panic!("EXPIRETIME must reply an integer");
+        };
+        assert!((at - expected).abs() <= 1);
+
+        assert_eq!(run(&backend, &["persist", "k"])?, RespFrame::Integer(1));
+        assert_eq!(run(&backend, &["persist", "k"])?, RespFrame::Integer(0));
+        assert_eq!(run(&backend, &["ttl", "k"])?, RespFrame::Integer(-1));
+
+        // 过去的时间直接删除 key
+        assert_eq!(
+            run(&backend, &["pexpireat", "k", "1"])?,
+            RespFrame::Integer(1)
+        );
+        assert_eq!(run(&backend, &["ttl", "k"])?, RespFrame::Integer(-2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_expire_conditions() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("k".into(), "v".into());
+        assert_eq!(
+            run(&backend, &["expire", "k", "100", "xx"])?,
+            RespFrame::Integer(0)
+        );
+        assert_eq!(
+            run(&backend, &["expire", "k", "100", "gt"])?,
+            RespFrame::Integer(0)
+        );
+        assert_eq!(
+            run(&backend, &["expire", "k", "100", "nx"])?,
+            RespFrame::Integer(1)
+        );
+        assert_eq!(
+            run(&backend, &["expire", "k", "200", "nx"])?,
+            RespFrame::Integer(0)
+        );
+        assert_eq!(
+            run(&backend, &["expire", "k", "50", "gt"])?,
+            RespFrame::Integer(0)
+        );
+        assert_eq!(
+            run(&backend, &["expire", "k", "200", "XX", "GT"])?,
+            RespFrame::Integer(1)
+        );
+        assert_eq!(
+            run(&backend, &["pexpire", "k", "300000", "lt"])?,
+            RespFrame::Integer(0)
+        );
+        assert_eq!(
+            run(&backend, &["expire", "k", "150", "lt"])?,
+            RespFrame::Integer(1)
+        );
+        assert_eq!(run(&backend, &["ttl", "k"])?, RespFrame::Integer(150));
+        Ok(())
+    }
+
+    #[test]
+    fn test_expire_errors() {
+        assert_eq!(
+            error(&["expire", "k", "1", "nx", "xx"]),
+            b"-ERR NX and XX, GT or LT options at the same time are not compatible\r\n"
+        );
+        assert_eq!(
+            error(&["expire", "k", "1", "gt", "lt"]),
+            b"-ERR GT and LT options at the same time are not compatible\r\n"
+        );
+        assert_eq!(
+            error(&["expire", "k", "1", "foo"]),
+            b"-ERR Unsupported option foo\r\n"
+        );
+        assert_eq!(
+            error(&["expire", "k", "abc"]),
+            b"-ERR value is not an integer or out of range\r\n"
+        );
+        assert_eq!(
+            error(&["expire", "k", "9223372036854775807"]),
+            b"-ERR invalid expire time in 'expire' command\r\n"
+        );
+    }
+}
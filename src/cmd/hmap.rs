@@ -1,4 +1,4 @@
-use super::{resp_ok, CommandError};
+use super::{bulk_arg, CommandError};
 use crate::{
     Backend, BulkString, CommandExecutor, HGet, HGetAll, HSet, RespArray, RespFrame, RespNull,
 };
@@ -6,42 +6,38 @@ use crate::{
 impl CommandExecutor for HGet {
     fn execute(&self, backend: &Backend) -> RespFrame {
         match backend.hget(&self.key, &self.field) {
-            Some(value) => value,
-            None => RespNull.into(),
+            Ok(Some(value)) => BulkString::new(value).into(),
+            Ok(None) => RespNull.into(),
+            Err(e) => e.into(),
         }
     }
 }
 
 impl CommandExecutor for HSet {
     fn execute(&self, backend: &Backend) -> RespFrame {
-        backend.hset(self.key.clone(), self.field.clone(), self.value.clone());
-        resp_ok().clone()
+        // 和 redis 一样回复新增的 field 的个数
+        match backend.hset(self.key.clone(), self.fields.iter().cloned()) {
+            Ok(added) => RespFrame::Integer(added as i64),
+            Err(e) => e.into(),
+        }
     }
 }
 
 impl CommandExecutor for HGetAll {
     fn execute(&self, backend: &Backend) -> RespFrame {
-        let hmap = backend.hmap.get(&self.key);
-
-        match hmap {
-            Some(hmap) => {
-                let mut data = Vec::with_capacity(hmap.len());
-                for v in hmap.iter() {
-                    let key = v.key().to_owned();
-                    data.push((key, v.value().clone()));
-                }
-                if self.sort {
-                    data.sort_by(|a, b| a.0.cmp(&b.0));
-                }
-                let ret = data
-                    .into_iter()
-                    .flat_map(|(k, v)| vec![BulkString::from(k).into(), v])
-                    .collect::<Vec<RespFrame>>();
-
-                RespArray::new(ret).into()
-            }
-            None => RespArray::new([]).into(),
+        let mut data = match backend.hgetall(&self.key) {
+            Ok(data) => data,
+            Err(e) => return e.into(),
+        };
+        if self.sort {
+            data.sort_by(|a, b| a.0.cmp(&b.0));
         }
+        let ret = data
+            .into_iter()
+            .flat_map(|(k, v)| [BulkString::new(k).into(), BulkString::new(v).into()])
+            .collect::<Vec<RespFrame>>();
+
+        RespArray::new(ret).into()
     }
 }
 
@@ -49,22 +45,10 @@ impl TryFrom<RespArray> for HGet {
     type Error = CommandError;
 
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        if let Some(RespFrame::BulkString(key)) = value.get(1) {
-            if let Some(RespFrame::BulkString(field)) = value.get(2) {
-                Ok(Self {
-                    key: key.to_string(),
-                    field: field.to_string(),
-                })
-            } else {
-                Err(CommandError::InvalidCommand(
-                    "HGET command must have a field".to_string(),
-                ))
-            }
-        } else {
-            Err(CommandError::InvalidCommand(
-                "HGET command must have a key".to_string(),
-            ))
-        }
+        Ok(Self {
+            key: bulk_arg(&value, 1)?,
+            field: bulk_arg(&value, 2)?,
+        })
     }
 }
 
@@ -72,29 +56,18 @@ impl TryFrom<RespArray> for HSet {
     type Error = CommandError;
 
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        if let Some(RespFrame::BulkString(key)) = value.get(1) {
-            if let Some(RespFrame::BulkString(field)) = value.get(2) {
-                if let Some(val) = value.get(3) {
-                    Ok(Self {
-                        key: key.to_string(),
-                        field: field.to_string(),
-                        value: val.clone(),
-                    })
-                } else {
-                    Err(CommandError::InvalidCommand(
-                        "HSET command must have a value".to_string(),
-                    ))
-                }
-            } else {
-                Err(CommandError::InvalidCommand(
-                    "HSET command must have a field".to_string(),
-                ))
-            }
-        } else {
-            Err(CommandError::InvalidCommand(
-                "HSET command must have a key".to_string(),
-            ))
+        // field 和 value 必须成对出现
+        if value.len() < 4 || !value.len().is_multiple_of(2) {
+            return Err(CommandError::WrongArity("hset"));
         }
+        let fields = (2..value.len())
+            .step_by(2)
+            .map(|i| Ok((bulk_arg(&value, i)?, bulk_arg(&value, i + 1)?)))
+            .collect::<Result<_, CommandError>>()?;
+        Ok(Self {
+            key: bulk_arg(&value, 1)?,
+            fields,
+        })
     }
 }
 
@@ -102,16 +75,10 @@ impl TryFrom<RespArray> for HGetAll {
     type Error = CommandError;
 
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        if let Some(RespFrame::BulkString(key)) = value.get(1) {
-            Ok(Self {
-                key: key.to_string(),
-                sort: false,
-            })
-        } else {
-            Err(CommandError::InvalidCommand(
-                "HGETALL command must have a key".to_string(),
-            ))
-        }
+        Ok(Self {
+            key: bulk_arg(&value, 1)?,
+            sort: false,
+        })
     }
 }
 
@@ -120,35 +87,34 @@ mod tests {
     use crate::BulkString;
 
     use super::*;
+    use crate::cmd::test_utils::{error, run};
     use anyhow::Result;
 
     #[test]
     fn test_hset_hget_hgetall_commands() -> Result<()> {
         let backend = crate::Backend::new();
         let cmd = HSet {
-            key: "map".to_string(),
-            field: "hello".to_string(),
-            value: RespFrame::BulkString(b"world".into()),
+            key: "map".into(),
+            fields: vec![("hello".into(), "world".into())],
         };
         let result = cmd.execute(&backend);
-        assert_eq!(result, resp_ok().clone());
+        assert_eq!(result, RespFrame::Integer(1));
 
         let cmd = HSet {
-            key: "map".to_string(),
-            field: "hello1".to_string(),
-            value: RespFrame::BulkString(b"world1".into()),
+            key: "map".into(),
+            fields: vec![("hello1".into(), "world1".into())],
         };
         cmd.execute(&backend);
 
         let cmd = HGet {
-            key: "map".to_string(),
-            field: "hello".to_string(),
+            key: "map".into(),
+            field: "hello".into(),
         };
         let result = cmd.execute(&backend);
         assert_eq!(result, RespFrame::BulkString(b"world".into()));
 
         let cmd = HGetAll {
-            key: "map".to_string(),
+            key: "map".into(),
             sort: true,
         };
         let result = cmd.execute(&backend);
@@ -162,4 +128,33 @@ mod tests {
         assert_eq!(result, expected.into());
         Ok(())
     }
+
+    #[test]
+    fn test_hset_multiple_fields() -> Result<()> {
+        let backend = crate::Backend::new();
+        assert_eq!(
+            run(&backend, &["hset", "map", "f1", "v1", "f2", "v2"])?,
+            RespFrame::Integer(2)
+        );
+        // 已经存在的 field 只更新，不计数
+        assert_eq!(
+            run(
+                &backend,
+                &["hset", "map", "f2", "v3", "f3", "v3", "f3", "v4"]
+            )?,
+            RespFrame::Integer(1)
+        );
+        assert_eq!(
+            run(&backend, &["hget", "map", "f3"])?,
+            BulkString::from("v4").into()
+        );
+        assert_eq!(
+            run(&backend, &["hget", "map", "f2"])?,
+            BulkString::from("v3").into()
+        );
+        let wrong_arity = b"-ERR wrong number of arguments for 'hset' command\r\n";
+        assert_eq!(error(&["hset", "map", "f1", "v1", "f2"]), wrong_arity);
+        assert_eq!(error(&["hset", "map", "f1"]), wrong_arity);
+        Ok(())
+    }
 }
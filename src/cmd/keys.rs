@@ -0,0 +1,65 @@
+use super::{bulk_arg, CommandError};
+use crate::{Backend, CommandExecutor, Del, RespArray, RespFrame, SimpleString, Type};
+
+impl CommandExecutor for Del {
+    fn execute(&self, backend: &Backend) -> RespFrame {
+        let deleted = self.keys.iter().filter(|key| backend.del(key)).count();
+        RespFrame::Integer(deleted as i64)
+    }
+}
+
+impl CommandExecutor for Type {
+    fn execute(&self, backend: &Backend) -> RespFrame {
+        SimpleString::new(backend.key_type(&self.key).unwrap_or("none")).into()
+    }
+}
+
+impl TryFrom<RespArray> for Del {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let keys = (1..value.len())
+            .map(|i| bulk_arg(&value, i))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { keys })
+    }
+}
+
+impl TryFrom<RespArray> for Type {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key: bulk_arg(&value, 1)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, Command, RespEncode};
+    use anyhow::Result;
+
+    #[test]
+    fn test_del_type_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("s".into(), "v".into());
+        backend.hset("h".into(), [("f".into(), "v".into())])?;
+
+        let cmd = Type { key: "h".into() };
+        assert_eq!(cmd.execute(&backend).encode(), b"+hash\r\n");
+
+        let cmd = Command::try_from(RespArray::new([
+            BulkString::from("del").into(),
+            BulkString::from("s").into(),
+            BulkString::from("h").into(),
+            BulkString::from("none").into(),
+        ]))?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+
+        let cmd = Type { key: "s".into() };
+        assert_eq!(cmd.execute(&backend).encode(), b"+none\r\n");
+        Ok(())
+    }
+}
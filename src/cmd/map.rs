@@ -1,19 +1,57 @@
-use super::{resp_ok, CommandError};
-use crate::{Backend, CommandExecutor, Get, RespArray, RespFrame, RespNull, Set};
+use super::{bulk_arg, expire::deadline, int_arg, resp_ok, CommandError};
+use crate::{
+    now_ms, Backend, BulkString, CommandExecutor, Get, RespArray, RespFrame, RespMap, RespNull,
+    Set, SetCondition, SetNx, SetOptions, SetTtl,
+};
 
 impl CommandExecutor for Get {
+    // key 有过期时间时，回复带上剩余的毫秒数作为 attribute，RESP2 下会被去掉
     fn execute(&self, backend: &Backend) -> RespFrame {
         match backend.get(&self.key) {
-            Some(value) => value,
-            None => RespNull.into(),
+            Ok(Some(value)) => {
+                let frame = RespFrame::from(BulkString::new(value));
+                match backend.expire_time(&self.key).flatten() {
+                    Some(at) => {
+                        let mut attributes = RespMap::new();
+                        attributes.insert("pttl".into(), (at - now_ms()).max(0).into());
+                        frame.with_attributes(attributes)
+                    }
+                    None => frame,
+                }
+            }
+            Ok(None) => RespNull.into(),
+            Err(e) => e.into(),
         }
     }
 }
 
 impl CommandExecutor for Set {
     fn execute(&self, backend: &Backend) -> RespFrame {
-        backend.set(self.key.clone(), self.value.clone());
-        resp_ok().clone()
+        let (written, old) =
+            match backend.set_with(self.key.clone(), self.value.clone(), &self.options) {
+                Ok(ret) => ret,
+                Err(e) => return e.into(),
+            };
+        // GET 返回旧的值，否则 NX/XX 条件不满足时返回 nil
+        match (self.options.get, old) {
+            (true, Some(old)) => BulkString::new(old).into(),
+            (true, None) => RespNull.into(),
+            (false, _) if written => resp_ok().clone(),
+            (false, _) => RespNull.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SetNx {
+    fn execute(&self, backend: &Backend) -> RespFrame {
+        let options = SetOptions {
+            condition: Some(SetCondition::Nx),
+            ..Default::default()
+        };
+        match backend.set_with(self.key.clone(), self.value.clone(), &options) {
+            Ok((written, _)) => RespFrame::Integer(written as i64),
+            Err(e) => e.into(),
+        }
     }
 }
 
@@ -21,63 +59,295 @@ impl TryFrom<RespArray> for Get {
     type Error = CommandError;
 
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        if let Some(RespFrame::BulkString(key)) = value.get(1) {
-            Ok(Self {
-                key: key.to_string(),
-            })
-        } else {
-            Err(CommandError::InvalidCommand(
-                "GET command must have a key".to_string(),
-            ))
-        }
+        Ok(Self {
+            key: bulk_arg(&value, 1)?,
+        })
     }
 }
 
+// SET key value [NX | XX] [GET] [EX seconds | PX milliseconds |
+//     EXAT unix-time-seconds | PXAT unix-time-milliseconds | KEEPTTL]
 impl TryFrom<RespArray> for Set {
     type Error = CommandError;
 
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        if let Some(RespFrame::BulkString(key)) = value.get(1) {
-            if let Some(val) = value.get(2) {
-                Ok(Self {
-                    key: key.to_string(),
-                    value: val.clone(),
-                })
-            } else {
-                Err(CommandError::InvalidCommand(
-                    "SET command must have a value".to_string(),
-                ))
+        let syntax_error = || CommandError::InvalidArguments("syntax error".to_string());
+        let mut options = SetOptions::default();
+        // 和 redis 一样，同一个过期选项可以重复，最后一个有效
+        let mut ttl_option: Option<Vec<u8>> = None;
+        let mut i = 3;
+        while i < value.len() {
+            let option = bulk_arg(&value, i)?.to_ascii_uppercase();
+            match option.as_slice() {
+                b"NX" | b"XX" => {
+                    let condition = if option == b"NX" {
+                        SetCondition::Nx
+                    } else {
+                        SetCondition::Xx
+                    };
+                    if options.condition.is_some_and(|c| c != condition) {
+                        return Err(syntax_error());
+                    }
+                    options.condition = Some(condition);
+                }
+                b"GET" => options.get = true,
+                b"KEEPTTL" | b"EX" | b"PX" | b"EXAT" | b"PXAT" => {
+                    if ttl_option.as_ref().is_some_and(|o| *o != option) {
+                        return Err(syntax_error());
+                    }
+                    let ttl = match option.as_slice() {
+                        b"KEEPTTL" => SetTtl::Keep,
+                        _ => {
+                            i += 1;
+                            if i >= value.len() {
+                                return Err(syntax_error());
+                            }
+                            let (unit_ms, absolute) = match option.as_slice() {
+                                b"EX" => (1000, false),
+                                b"PX" => (1, false),
+                                b"EXAT" => (1000, true),
+                                _ => (1, true),
+                            };
+                            SetTtl::At(expire_arg(&value, i, "set", unit_ms, absolute)?)
+                        }
+                    };
+                    options.ttl = Some(ttl);
+                    ttl_option = Some(option);
+                }
+                _ => return Err(syntax_error()),
             }
-        } else {
-            Err(CommandError::InvalidCommand(
-                "SET command must have a key".to_string(),
-            ))
+            i += 1;
         }
+        Ok(Self {
+            key: bulk_arg(&value, 1)?,
+            value: bulk_arg(&value, 2)?,
+            options,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SetNx {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key: bulk_arg(&value, 1)?,
+            value: bulk_arg(&value, 2)?,
+        })
     }
 }
 
+// SETEX key seconds value / PSETEX key milliseconds value
+pub(super) fn parse_setex(
+    value: RespArray,
+    name: &'static str,
+    unit_ms: i64,
+) -> Result<Set, CommandError> {
+    let at = expire_arg(&value, 2, name, unit_ms, false)?;
+    Ok(Set {
+        key: bulk_arg(&value, 1)?,
+        value: bulk_arg(&value, 3)?,
+        options: SetOptions {
+            ttl: Some(SetTtl::At(at)),
+            ..Default::default()
+        },
+    })
+}
+
+// SET 的过期时间必须是正数
+fn expire_arg(
+    value: &RespArray,
+    index: usize,
+    name: &'static str,
+    unit_ms: i64,
+    absolute: bool,
+) -> Result<i64, CommandError> {
+    let when = int_arg(value, index)?;
+    if when <= 0 {
+        return Err(CommandError::InvalidArguments(format!(
+            "invalid expire time in '{name}' command"
+        )));
+    }
+    deadline(when, name, unit_ms, absolute)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Backend;
+    use crate::{
+        cmd::test_utils::{error, run},
+        Backend, RespEncode,
+    };
     use anyhow::Result;
 
     #[test]
     fn test_set_get_command() -> Result<()> {
         let backend = Backend::new();
         let cmd = Set {
-            key: "hello".to_string(),
-            value: RespFrame::BulkString(b"world".into()),
+            key: "hello".into(),
+            value: "world".into(),
+            ..Default::default()
         };
         let result = cmd.execute(&backend);
         assert_eq!(result, resp_ok().clone());
 
         let cmd = Get {
-            key: "hello".to_string(),
+            key: "hello".into(),
         };
         let result = cmd.execute(&backend);
         assert_eq!(result, RespFrame::BulkString(b"world".into()));
 
+        let cmd = Set {
+            key: "empty".into(),
+            value: "".into(),
+            ..Default::default()
+        };
+        cmd.execute(&backend);
+        let cmd = Get {
+            key: "empty".into(),
+        };
+        let result = cmd.execute(&backend);
+        assert_eq!(result.encode(), b"$0\r\n\r\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_ttl_attribute() -> Result<()> {
+        let backend = Backend::new();
+        run(&backend, &["set", "k", "v", "px", "10000"])?;
+        let RespFrame::Attribute(reply) = run(&backend, &["get", "k"])? else {
+            panic!("GET of a key with expire time must have attributes");
+        };
+        let Some(RespFrame::Integer(pttl)) = reply.attributes().get(&RespFrame::from("pttl"))
+        else {
+            panic!("pttl attribute must be an integer");
+        };
+        assert!((1..=10_000).contains(pttl));
+        assert_eq!(reply.frame(), &BulkString::from("v").into());
+
+        // RESP2 下没有 attribute
+        let frame = run(&backend, &["get", "k"])?.into_resp2();
+        assert_eq!(frame, BulkString::from("v").into());
+        run(&backend, &["persist", "k"])?;
+        assert_eq!(run(&backend, &["get", "k"])?, BulkString::from("v").into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_wrong_type() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset("map".into(), [("f".into(), "1".into())])?;
+        let cmd = Get { key: "map".into() };
+        assert_eq!(
+            cmd.execute(&backend).encode(),
+            b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_rejects_non_bulk_value() {
+        let cmd = RespArray::new([
+            BulkString::from("set").into(),
+            BulkString::from("k").into(),
+            RespArray::new([1.into()]).into(),
+        ]);
+        assert!(Set::try_from(cmd).is_err());
+    }
+
+    #[test]
+    fn test_set_options() -> Result<()> {
+        let backend = Backend::new();
+        // 分布式锁
+        assert_eq!(
+            run(&backend, &["set", "lock", "1", "NX", "PX", "30000"])?,
+            resp_ok().clone()
+        );
+        assert_eq!(
+            run(&backend, &["set", "lock", "2", "nx", "px", "30000"])?,
+            RespNull.into()
+        );
+        assert!(backend.expire_time(b"lock").unwrap().is_some());
+
+        assert_eq!(run(&backend, &["set", "k", "v", "xx"])?, RespNull.into());
+        assert_eq!(run(&backend, &["set", "k", "v", "get"])?, RespNull.into());
+        assert_eq!(
+            run(&backend, &["set", "k", "v2", "get", "ex", "100"])?,
+            BulkString::from("v").into()
+        );
+        assert!(backend.expire_time(b"k").unwrap().is_some());
+        run(&backend, &["set", "k", "v3", "keepttl"])?;
+        assert!(backend.expire_time(b"k").unwrap().is_some());
+        run(&backend, &["set", "k", "v4"])?;
+        assert_eq!(backend.expire_time(b"k"), Some(None));
+
+        let at = (crate::now_ms() / 1000 + 100).to_string();
+        run(&backend, &["set", "k", "v", "exat", &at])?;
+        assert_eq!(
+            backend.expire_time(b"k"),
+            Some(Some(at.parse::<i64>()? * 1000))
+        );
+        run(&backend, &["set", "k", "v", "pxat", "1"])?;
+        assert_eq!(backend.get(b"k")?, None);
+
+        backend.hset("h".into(), [("f".into(), "v".into())])?;
+        assert_eq!(
+            run(&backend, &["set", "h", "v", "get"])?.encode(),
+            b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_option_errors() {
+        let syntax_error = b"-ERR syntax error\r\n";
+        assert_eq!(error(&["set", "k", "v", "nx", "xx"]), syntax_error);
+        assert_eq!(
+            error(&["set", "k", "v", "ex", "10", "px", "10"]),
+            syntax_error
+        );
+        assert_eq!(
+            error(&["set", "k", "v", "keepttl", "ex", "10"]),
+            syntax_error
+        );
+        assert_eq!(error(&["set", "k", "v", "ex"]), syntax_error);
+        assert_eq!(error(&["set", "k", "v", "foo"]), syntax_error);
+        assert_eq!(
+            error(&["set", "k", "v", "ex", "abc"]),
+            b"-ERR value is not an integer or out of range\r\n"
+        );
+        assert_eq!(
+            error(&["set", "k", "v", "px", "0"]),
+            b"-ERR invalid expire time in 'set' command\r\n"
+        );
+        assert_eq!(
+            error(&["setex", "k", "-1", "v"]),
+            b"-ERR invalid expire time in 'setex' command\r\n"
+        );
+    }
+
+    #[test]
+    fn test_setnx_setex_psetex() -> Result<()> {
+        let backend = Backend::new();
+        assert_eq!(run(&backend, &["setnx", "k", "v"])?, RespFrame::Integer(1));
+        assert_eq!(run(&backend, &["setnx", "k", "v2"])?, RespFrame::Integer(0));
+        assert_eq!(backend.get(b"k")?, Some("v".into()));
+
+        assert_eq!(
+            run(&backend, &["setex", "k", "100", "v3"])?,
+            resp_ok().clone()
+        );
+        assert_eq!(backend.get(b"k")?, Some("v3".into()));
+        let at = backend.expire_time(b"k").unwrap().unwrap();
+        assert!(at > crate::now_ms() + 99_000);
+
+        assert_eq!(
+            run(&backend, &["psetex", "k", "100", "v4"])?,
+            resp_ok().clone()
+        );
+        let at = backend.expire_time(b"k").unwrap().unwrap();
+        assert!(at <= crate::now_ms() + 100);
         Ok(())
     }
 }
@@ -1,11 +1,23 @@
+mod connection;
+mod counter;
 mod error;
+mod expire;
 mod hmap;
+mod keys;
 mod map;
+mod server;
+mod table;
 
-use crate::{Backend, RespArray, RespFrame, SimpleString};
+use crate::{
+    backend::{parse_float, parse_int},
+    Backend, RespArray, RespFrame, RespProtocol, SetOptions, SimpleError, SimpleString,
+};
+use bytes::Bytes;
 use enum_dispatch::enum_dispatch;
 use error::CommandError;
-use std::{str::from_utf8, sync::OnceLock};
+use std::sync::OnceLock;
+
+pub use table::{lookup_command, CommandFlags, CommandSpec, KeySpec};
 
 pub fn resp_ok() -> &'static RespFrame {
     static RESP_OK: OnceLock<RespFrame> = OnceLock::new();
@@ -22,9 +34,20 @@ pub trait CommandExecutor {
 pub enum Command {
     Get(Get),
     Set(Set),
+    SetNx(SetNx),
+    IncrBy(IncrBy),
+    IncrByFloat(IncrByFloat),
     HGet(HGet),
     HSet(HSet),
     HGetAll(HGetAll),
+    Hello(Hello),
+    Info(Info),
+    Del(Del),
+    Type(Type),
+    Expire(Expire),
+    Ttl(Ttl),
+    ExpireTime(ExpireTime),
+    Persist(Persist),
 
     // unrecognized command
     Unrecognized(Unrecognized),
@@ -32,36 +55,140 @@ pub enum Command {
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Get {
-    pub key: String,
+    pub key: Bytes,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+// SET/SETEX/PSETEX
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct Set {
-    pub key: String,
-    pub value: RespFrame,
+    pub key: Bytes,
+    pub value: Bytes,
+    pub options: SetOptions,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetNx {
+    pub key: Bytes,
+    pub value: Bytes,
+}
+
+// INCR/DECR/INCRBY/DECRBY
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncrBy {
+    pub key: Bytes,
+    pub delta: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncrByFloat {
+    pub key: Bytes,
+    pub delta: f64,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct HGet {
-    pub key: String,
-    pub field: String,
+    pub key: Bytes,
+    pub field: Bytes,
 }
 
+// HSET key field value [field value ...]
 #[derive(Debug, Clone, PartialEq)]
 pub struct HSet {
-    pub key: String,
-    pub field: String,
-    pub value: RespFrame,
+    pub key: Bytes,
+    pub fields: Vec<(Bytes, Bytes)>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct HGetAll {
-    key: String,
+    key: Bytes,
     sort: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct Unrecognized;
+pub struct Del {
+    pub keys: Vec<Bytes>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Type {
+    pub key: Bytes,
+}
+
+// EXPIRE/PEXPIRE/EXPIREAT/PEXPIREAT，解析时都转换成 unix 毫秒
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expire {
+    pub key: Bytes,
+    pub at: i64,
+    pub conditions: Vec<ExpireCondition>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExpireCondition {
+    // NX: 没有过期时间时才设置
+    Nx,
+    // XX: 有过期时间时才设置
+    Xx,
+    // GT: 新的过期时间更晚时才设置，没有过期时间视为无限长
+    Gt,
+    // LT: 新的过期时间更早时才设置
+    Lt,
+}
+
+// TTL/PTTL
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ttl {
+    pub key: Bytes,
+    pub millis: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpireTime {
+    pub key: Bytes,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Persist {
+    pub key: Bytes,
+}
+
+// HELLO [protover]，切换连接的协议版本
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hello {
+    pub protover: Option<i64>,
+}
+
+// INFO [section ...]，没有指定时返回所有的 section
+#[derive(Debug, Clone, PartialEq)]
+pub struct Info {
+    pub sections: Vec<String>,
+}
+
+// 未知命令，保存命令名和参数用来回复错误
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unrecognized {
+    pub name: String,
+    pub args: Vec<String>,
+}
+
+// 在连接上执行命令的结果：回复和之后连接使用的协议，只有 HELLO 会切换协议
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandReply {
+    pub frame: RespFrame,
+    pub protocol: RespProtocol,
+}
+
+impl Command {
+    // protocol 是连接当前使用的协议
+    pub fn execute_on(&self, backend: &Backend, protocol: RespProtocol) -> CommandReply {
+        match self {
+            Command::Hello(hello) => hello.negotiate(protocol),
+            cmd => CommandReply {
+                frame: cmd.execute(backend),
+                protocol,
+            },
+        }
+    }
+}
 
 impl TryFrom<RespFrame> for Command {
     type Error = CommandError;
@@ -81,23 +208,10 @@ impl TryFrom<RespArray> for Command {
 
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
         match value.first() {
-            Some(RespFrame::BulkString(ref cmd)) => {
-                let cmd = from_utf8(cmd.as_ref())
-                    .map_err(|_| {
-                        CommandError::InvalidCommand(
-                            "Command must be a valid UTF-8 string".to_string(),
-                        )
-                    })?
-                    .to_uppercase();
-                match cmd.as_str() {
-                    "GET" => Ok(Get::try_from(value)?.into()),
-                    "SET" => Ok(Set::try_from(value)?.into()),
-                    "HGET" => Ok(HGet::try_from(value)?.into()),
-                    "HSET" => Ok(HSet::try_from(value)?.into()),
-                    "HGETALL" => Ok(HGetAll::try_from(value)?.into()),
-                    _ => Ok(Unrecognized.into()),
-                }
-            }
+            Some(RespFrame::BulkString(name)) => match lookup_command(name) {
+                Some(spec) => spec.parse(value),
+                None => Ok(Unrecognized::from(value).into()),
+            },
             _ => Err(CommandError::InvalidCommand(
                 "Command must have a BulkString as the first argument".to_string(),
             )),
@@ -105,27 +219,103 @@ impl TryFrom<RespArray> for Command {
     }
 }
 
+// 参数必须是 bulk string，按字节保存，不做任何转换
+fn bulk_arg(value: &RespArray, index: usize) -> Result<Bytes, CommandError> {
+    match value.get(index) {
+        Some(RespFrame::BulkString(arg)) => Ok(Bytes::clone(arg)),
+        _ => Err(CommandError::InvalidArguments(
+            "Protocol error: expected bulk string argument".to_string(),
+        )),
+    }
+}
+
+fn int_arg(value: &RespArray, index: usize) -> Result<i64, CommandError> {
+    parse_int(&bulk_arg(value, index)?).ok_or_else(|| {
+        CommandError::InvalidArguments("value is not an integer or out of range".to_string())
+    })
+}
+
+fn float_arg(value: &RespArray, index: usize) -> Result<f64, CommandError> {
+    parse_float(&bulk_arg(value, index)?)
+        .ok_or_else(|| CommandError::InvalidArguments("value is not a valid float".to_string()))
+}
+
+impl From<RespArray> for Unrecognized {
+    fn from(value: RespArray) -> Self {
+        let mut args = value.iter().map(|arg| match arg {
+            RespFrame::BulkString(arg) => arg.to_string(),
+            arg => format!("{arg:?}"),
+        });
+        Self {
+            name: args.next().unwrap_or_default(),
+            args: args.collect(),
+        }
+    }
+}
+
+// 和 redis 一样，命令名和参数最多显示 128 个字符，换行替换成空格
+const MAX_ERROR_ARGS_LEN: usize = 128;
+
 impl CommandExecutor for Unrecognized {
     fn execute(&self, _backend: &Backend) -> RespFrame {
-        resp_ok().clone()
+        let truncate = |s: &str, max: usize| -> String {
+            s.chars()
+                .take(max)
+                .map(|c| if c == '\r' || c == '\n' { ' ' } else { c })
+                .collect()
+        };
+        let mut args = String::new();
+        for arg in &self.args {
+            let len = args.chars().count();
+            if len >= MAX_ERROR_ARGS_LEN {
+                break;
+            }
+            args.push_str(&format!("'{}' ", truncate(arg, MAX_ERROR_ARGS_LEN - len)));
+        }
+        SimpleError::new(format!(
+            "ERR unknown command '{}', with args beginning with: {args}",
+            truncate(&self.name, MAX_ERROR_ARGS_LEN)
+        ))
+        .into()
+    }
+}
+
+// 命令测试共用：按参数构造命令并执行，或者返回解析命令的错误回复
+#[cfg(test)]
+mod test_utils {
+    use super::*;
+    use crate::{BulkString, RespArray, RespEncode};
+
+    pub(super) fn command(args: &[&str]) -> RespArray {
+        let args: Vec<_> = args.iter().map(|a| BulkString::from(*a).into()).collect();
+        RespArray::new(args)
+    }
+
+    pub(super) fn run(backend: &Backend, args: &[&str]) -> anyhow::Result<RespFrame> {
+        Ok(Command::try_from(command(args))?.execute(backend))
+    }
+
+    pub(super) fn error(args: &[&str]) -> Vec<u8> {
+        RespFrame::from(Command::try_from(command(args)).unwrap_err()).encode()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Backend, RespArray, RespDecode, RespNull};
+    use crate::{Backend, BulkString, RespArray, RespDecode, RespEncode, RespNull};
     use anyhow::Result;
+    use bytes::BytesMut;
 
     #[test]
     fn test_command() -> Result<()> {
-        let buf = b"*2\r\n$3\r\nGET\r\n$5\r\nnnnnn\r\n";
-        let frame: RespFrame = RespArray::decode(buf)?.into();
+        let mut buf = BytesMut::from("*2\r\n$3\r\nGET\r\n$5\r\nnnnnn\r\n");
+        let frame: RespFrame = RespArray::decode(&mut buf)?.into();
         let cmd: Command = frame.try_into()?;
         assert_eq!(
             cmd,
             Command::Get(Get {
-                key: "nnnnn".to_string()
+                key: "nnnnn".into()
             })
         );
         let backend = Backend::new();
@@ -134,4 +324,59 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_command_error_reply() {
+        let cmd = RespArray::new([BulkString::from("get").into()]);
+        let err = Command::try_from(cmd).unwrap_err();
+        assert_eq!(err, CommandError::WrongArity("get"));
+        assert_eq!(
+            RespFrame::from(err).encode(),
+            b"-ERR wrong number of arguments for 'get' command\r\n"
+        );
+
+        let cmd = RespArray::new([
+            BulkString::from("hset").into(),
+            BulkString::from("map").into(),
+            1.into(),
+            BulkString::from("v").into(),
+        ]);
+        assert!(matches!(
+            Command::try_from(cmd),
+            Err(CommandError::InvalidArguments(_))
+        ));
+
+        let err = Command::try_from(RespFrame::from("get")).unwrap_err();
+        assert_eq!(
+            RespFrame::from(err).encode(),
+            b"-ERR Command must be an array\r\n"
+        );
+    }
+
+    #[test]
+    fn test_unrecognized_command() -> Result<()> {
+        let cmd = Command::try_from(RespArray::new([
+            BulkString::from("HSETT").into(),
+            BulkString::from("map").into(),
+            BulkString::from("a\r\nb").into(),
+        ]))?;
+        assert_eq!(
+            cmd,
+            Command::Unrecognized(Unrecognized {
+                name: "HSETT".to_string(),
+                args: vec!["map".to_string(), "a\r\nb".to_string()],
+            })
+        );
+        assert_eq!(
+            cmd.execute(&Backend::new()).encode(),
+            b"-ERR unknown command 'HSETT', with args beginning with: 'map' 'a  b' \r\n"
+        );
+
+        let cmd = Command::try_from(RespArray::new([BulkString::from("flushall").into()]))?;
+        assert_eq!(
+            cmd.execute(&Backend::new()).encode(),
+            b"-ERR unknown command 'flushall', with args beginning with: \r\n"
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,104 @@
+use std::fmt::Write;
+
+use super::{bulk_arg, CommandError};
+use crate::{Backend, CommandExecutor, Info, RespArray, RespFrame, RespVerbatimString};
+
+// 和 redis 一样，INFO 的回复是 "# Section" 开头、"key:value" 格式的文本，
+// RESP3 下是 verbatim string，RESP2 下降级为 bulk string
+const SECTIONS: &[&str] = &["server", "keyspace"];
+
+impl CommandExecutor for Info {
+    fn execute(&self, backend: &Backend) -> RespFrame {
+        let all = self.sections.is_empty()
+            || self
+                .sections
+                .iter()
+                .any(|s| matches!(s.as_str(), "all" | "default" | "everything"));
+        let mut info = String::new();
+        // 不认识的 section 忽略
+        for section in SECTIONS
+            .iter()
+            .filter(|s| all || self.sections.iter().any(|n| n == *s))
+        {
+            if !info.is_empty() {
+                info.push_str("\r\n");
+            }
+            // writing to a String never fails
+            let _ = write_section(section, backend, &mut info);
+        }
+        RespVerbatimString::text(info).into()
+    }
+}
+
+fn write_section(section: &str, backend: &Backend, info: &mut String) -> std::fmt::Result {
+    match section {
+        "server" => {
+            info.push_str("# Server\r\n");
+            write!(info, "redis_version:{}\r\n", env!("CARGO_PKG_VERSION"))?;
+            info.push_str("redis_mode:standalone\r\n");
+            write!(info, "process_id:{}\r\n", std::process::id())?;
+        }
+        "keyspace" => {
+            info.push_str("# Keyspace\r\n");
+            let keys = backend.keyspace.len();
+            if keys > 0 {
+                let expires = backend.expires.len();
+                write!(info, "db0:keys={keys},expires={expires},avg_ttl=0\r\n")?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+impl TryFrom<RespArray> for Info {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let sections = (1..value.len())
+            .map(|i| {
+                let section = bulk_arg(&value, i)?;
+                Ok(String::from_utf8_lossy(&section).to_lowercase())
+            })
+            .collect::<Result<_, CommandError>>()?;
+        Ok(Self { sections })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cmd::test_utils::run, now_ms};
+    use anyhow::Result;
+
+    fn info_text(frame: RespFrame) -> String {
+        let RespFrame::VerbatimString(info) = frame else {
+            panic!("INFO must reply a verbatim string");
+        };
+        assert_eq!(info.format(), b"txt");
+        String::from_utf8_lossy(info.data()).into_owned()
+    }
+
+    #[test]
+    fn test_info_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("a".into(), "1".into());
+        backend.set("b".into(), "2".into());
+        backend.expire_at(&"b".into(), now_ms() + 10_000, |_| true);
+
+        let info = info_text(run(&backend, &["info"])?);
+        assert!(info.starts_with("# Server\r\nredis_version:"));
+        assert!(info.ends_with("\r\n# Keyspace\r\ndb0:keys=2,expires=1,avg_ttl=0\r\n"));
+
+        let info = info_text(run(&backend, &["INFO", "Keyspace"])?);
+        assert_eq!(info, "# Keyspace\r\ndb0:keys=2,expires=1,avg_ttl=0\r\n");
+        assert_eq!(info_text(run(&backend, &["info", "foo"])?), "");
+
+        // RESP2 客户端收到 bulk string
+        assert!(matches!(
+            run(&backend, &["info"])?.into_resp2(),
+            RespFrame::BulkString(_)
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,352 @@
+use std::ops::BitOr;
+
+use super::{
+    counter::{parse_decrby, parse_incr},
+    expire::parse_expire,
+    map::parse_setex,
+    CommandError,
+};
+use crate::{
+    Command, Del, ExpireTime, Get, HGet, HGetAll, HSet, Hello, IncrBy, IncrByFloat, Info, Persist,
+    RespArray, Set, SetNx, Ttl, Type,
+};
+
+// 命令表，和 redis 的 commands.def 一样记录每个命令的参数个数、标志和 key 的位置，
+// Command::try_from 通过命令表分发和检查参数个数
+static COMMAND_TABLE: &[CommandSpec] = &[
+    CommandSpec {
+        name: "get",
+        arity: 2,
+        flags: CommandFlags::READONLY.union(CommandFlags::FAST),
+        keys: KeySpec::single(1),
+        parse: |v| Ok(Get::try_from(v)?.into()),
+    },
+    CommandSpec {
+        name: "set",
+        arity: -3,
+        flags: CommandFlags::WRITE,
+        keys: KeySpec::single(1),
+        parse: |v| Ok(Set::try_from(v)?.into()),
+    },
+    CommandSpec {
+        name: "setnx",
+        arity: 3,
+        flags: CommandFlags::WRITE.union(CommandFlags::FAST),
+        keys: KeySpec::single(1),
+        parse: |v| Ok(SetNx::try_from(v)?.into()),
+    },
+    CommandSpec {
+        name: "setex",
+        arity: 4,
+        flags: CommandFlags::WRITE,
+        keys: KeySpec::single(1),
+        parse: |v| Ok(parse_setex(v, "setex", 1000)?.into()),
+    },
+    CommandSpec {
+        name: "psetex",
+        arity: 4,
+        flags: CommandFlags::WRITE,
+        keys: KeySpec::single(1),
+        parse: |v| Ok(parse_setex(v, "psetex", 1)?.into()),
+    },
+    CommandSpec {
+        name: "incr",
+        arity: 2,
+        flags: CommandFlags::WRITE.union(CommandFlags::FAST),
+        keys: KeySpec::single(1),
+        parse: |v| Ok(parse_incr(v, 1)?.into()),
+    },
+    CommandSpec {
+        name: "decr",
+        arity: 2,
+        flags: CommandFlags::WRITE.union(CommandFlags::FAST),
+        keys: KeySpec::single(1),
+        parse: |v| Ok(parse_incr(v, -1)?.into()),
+    },
+    CommandSpec {
+        name: "incrby",
+        arity: 3,
+        flags: CommandFlags::WRITE.union(CommandFlags::FAST),
+        keys: KeySpec::single(1),
+        parse: |v| Ok(IncrBy::try_from(v)?.into()),
+    },
+    CommandSpec {
+        name: "decrby",
+        arity: 3,
+        flags: CommandFlags::WRITE.union(CommandFlags::FAST),
+        keys: KeySpec::single(1),
+        parse: |v| Ok(parse_decrby(v)?.into()),
+    },
+    CommandSpec {
+        name: "incrbyfloat",
+        arity: 3,
+        flags: CommandFlags::WRITE.union(CommandFlags::FAST),
+        keys: KeySpec::single(1),
+        parse: |v| Ok(IncrByFloat::try_from(v)?.into()),
+    },
+    CommandSpec {
+        name: "hget",
+        arity: 3,
+        flags: CommandFlags::READONLY.union(CommandFlags::FAST),
+        keys: KeySpec::single(1),
+        parse: |v| Ok(HGet::try_from(v)?.into()),
+    },
+    CommandSpec {
+        name: "hset",
+        arity: -4,
+        flags: CommandFlags::WRITE.union(CommandFlags::FAST),
+        keys: KeySpec::single(1),
+        parse: |v| Ok(HSet::try_from(v)?.into()),
+    },
+    CommandSpec {
+        name: "hgetall",
+        arity: 2,
+        flags: CommandFlags::READONLY,
+        keys: KeySpec::single(1),
+        parse: |v| Ok(HGetAll::try_from(v)?.into()),
+    },
+    CommandSpec {
+        name: "del",
+        arity: -2,
+        flags: CommandFlags::WRITE,
+        keys: KeySpec {
+            first: 1,
+            last: -1,
+            step: 1,
+        },
+        parse: |v| Ok(Del::try_from(v)?.into()),
+    },
+    CommandSpec {
+        name: "type",
+        arity: 2,
+        flags: CommandFlags::READONLY.union(CommandFlags::FAST),
+        keys: KeySpec::single(1),
+        parse: |v| Ok(Type::try_from(v)?.into()),
+    },
+    CommandSpec {
+        name: "expire",
+        arity: -3,
+        flags: CommandFlags::WRITE.union(CommandFlags::FAST),
+        keys: KeySpec::single(1),
+        parse: |v| Ok(parse_expire(v, "expire", 1000, false)?.into()),
+    },
+    CommandSpec {
+        name: "pexpire",
+        arity: -3,
+        flags: CommandFlags::WRITE.union(CommandFlags::FAST),
+        keys: KeySpec::single(1),
+        parse: |v| Ok(parse_expire(v, "pexpire", 1, false)?.into()),
+    },
+    CommandSpec {
+        name: "expireat",
+        arity: -3,
+        flags: CommandFlags::WRITE.union(CommandFlags::FAST),
+        keys: KeySpec::single(1),
+        parse: |v| Ok(parse_expire(v, "expireat", 1000, true)?.into()),
+    },
+    CommandSpec {
+        name: "pexpireat",
+        arity: -3,
+        flags: CommandFlags::WRITE.union(CommandFlags::FAST),
+        keys: KeySpec::single(1),
+        parse: |v| Ok(parse_expire(v, "pexpireat", 1, true)?.into()),
+    },
+    CommandSpec {
+        name: "ttl",
+        arity: 2,
+        flags: CommandFlags::READONLY.union(CommandFlags::FAST),
+        keys: KeySpec::single(1),
+        parse: |v| Ok(Ttl::try_from(v)?.into()),
+    },
+    CommandSpec {
+        name: "pttl",
+        arity: 2,
+        flags: CommandFlags::READONLY.union(CommandFlags::FAST),
+        keys: KeySpec::single(1),
+        parse: |v| {
+            let ttl = Ttl::try_from(v)?;
+            Ok(Ttl {
+                millis: true,
+                ..ttl
+            }
+            .into())
+        },
+    },
+    CommandSpec {
+        name: "expiretime",
+        arity: 2,
+        flags: CommandFlags::READONLY.union(CommandFlags::FAST),
+        keys: KeySpec::single(1),
+        parse: |v| Ok(ExpireTime::try_from(v)?.into()),
+    },
+    CommandSpec {
+        name: "persist",
+        arity: 2,
+        flags: CommandFlags::WRITE.union(CommandFlags::FAST),
+        keys: KeySpec::single(1),
+        parse: |v| Ok(Persist::try_from(v)?.into()),
+    },
+    CommandSpec {
+        name: "hello",
+        arity: -1,
+        flags: CommandFlags::FAST,
+        keys: KeySpec::NONE,
+        parse: |v| Ok(Hello::try_from(v)?.into()),
+    },
+    CommandSpec {
+        name: "info",
+        arity: -1,
+        flags: CommandFlags::READONLY,
+        keys: KeySpec::NONE,
+        parse: |v| Ok(Info::try_from(v)?.into()),
+    },
+];
+
+#[derive(Debug)]
+pub struct CommandSpec {
+    // lowercase name, also used in error replies
+    pub name: &'static str,
+    // 和 redis 一样包括命令名，N 表示正好 N 个，-N 表示至少 N 个
+    pub arity: i64,
+    pub flags: CommandFlags,
+    pub keys: KeySpec,
+    parse: fn(RespArray) -> Result<Command, CommandError>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandFlags(u8);
+
+// key 的位置：从 first 到 last（负数表示从后往前数），间隔 step，first 为 0 表示没有 key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeySpec {
+    pub first: usize,
+    pub last: isize,
+    pub step: usize,
+}
+
+// 查找命令，命令名不区分大小写
+pub fn lookup_command(name: &[u8]) -> Option<&'static CommandSpec> {
+    COMMAND_TABLE
+        .iter()
+        .find(|spec| spec.name.as_bytes().eq_ignore_ascii_case(name))
+}
+
+impl CommandSpec {
+    // 检查参数个数后解析命令
+    pub fn parse(&self, value: RespArray) -> Result<Command, CommandError> {
+        let argc = value.len() as i64;
+        if (self.arity > 0 && argc != self.arity) || argc < -self.arity {
+            return Err(CommandError::WrongArity(self.name));
+        }
+        (self.parse)(value)
+    }
+}
+
+impl CommandFlags {
+    pub const WRITE: Self = Self(1);
+    pub const READONLY: Self = Self(1 << 1);
+    pub const FAST: Self = Self(1 << 2);
+
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for CommandFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.union(rhs)
+    }
+}
+
+impl KeySpec {
+    pub const NONE: Self = Self {
+        first: 0,
+        last: 0,
+        step: 0,
+    };
+
+    pub const fn single(index: usize) -> Self {
+        Self {
+            first: index,
+            last: index as isize,
+            step: 1,
+        }
+    }
+
+    // positions of the keys in a command of argc arguments (including the name)
+    pub fn positions(&self, argc: usize) -> impl Iterator<Item = usize> {
+        let last = if self.last < 0 {
+            argc as isize + self.last
+        } else {
+            self.last
+        };
+        let end = match self.first {
+            0 => 0,
+            _ => (last.min(argc as isize - 1) + 1).max(0) as usize,
+        };
+        (self.first..end.max(self.first)).step_by(self.step.max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::test_utils::command;
+
+    #[test]
+    fn test_lookup_command() {
+        let spec = lookup_command(b"GeT").unwrap();
+        assert_eq!(spec.name, "get");
+        assert!(spec
+            .flags
+            .contains(CommandFlags::READONLY | CommandFlags::FAST));
+        assert!(!spec.flags.contains(CommandFlags::WRITE));
+        assert!(lookup_command(b"hsett").is_none());
+    }
+
+    #[test]
+    fn test_command_arity() {
+        let spec = lookup_command(b"get").unwrap();
+        assert!(spec.parse(command(&["get", "a"])).is_ok());
+        assert_eq!(
+            spec.parse(command(&["get", "a", "b", "c"])),
+            Err(CommandError::WrongArity("get"))
+        );
+        assert_eq!(
+            spec.parse(command(&["get"])),
+            Err(CommandError::WrongArity("get"))
+        );
+
+        let spec = lookup_command(b"hello").unwrap();
+        assert!(spec.parse(command(&["hello"])).is_ok());
+        assert!(spec.parse(command(&["hello", "3"])).is_ok());
+    }
+
+    #[test]
+    fn test_key_positions() {
+        assert_eq!(KeySpec::single(1).positions(3).collect::<Vec<_>>(), [1]);
+        assert_eq!(KeySpec::NONE.positions(3).count(), 0);
+
+        // MSET k1 v1 k2 v2
+        let spec = KeySpec {
+            first: 1,
+            last: -1,
+            step: 2,
+        };
+        assert_eq!(spec.positions(5).collect::<Vec<_>>(), [1, 3]);
+        // DEL k1 k2 k3
+        let spec = KeySpec {
+            first: 1,
+            last: -1,
+            step: 1,
+        };
+        assert_eq!(spec.positions(4).collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(spec.positions(1).count(), 0);
+    }
+}
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use simple_redis::{stream_handler, Backend};
+use simple_redis::{stream_handler, Backend, RespLimits};
 use tokio::net::TcpListener;
 use tracing::{info, warn};
 
@@ -12,12 +12,15 @@ async fn main() -> Result<()> {
     let listener = TcpListener::bind(addr).await?;
 
     let backend = Backend::new();
+    // 协议限制，和 redis 的 proto-max-bulk-len 等配置的默认值一样
+    let limits = RespLimits::default();
+    tokio::spawn(backend.clone().run_active_expire());
     loop {
         let (stream, raddr) = listener.accept().await?;
         info!("Accepted connection from: {}", raddr);
         let cloned_backend = backend.clone();
         tokio::spawn(async move {
-            match stream_handler(stream, cloned_backend).await {
+            match stream_handler(stream, cloned_backend, limits).await {
                 Ok(_) => {
                     info!("Connection from {} exited", raddr);
                 }
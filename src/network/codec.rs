@@ -0,0 +1,439 @@
+use anyhow::Result;
+use bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+
+use super::inline::split_args;
+use crate::{
+    resp::{find_crlf, is_frame_prefix, FrameHeader, FrameScanner},
+    BulkString, RespArray, RespEncode, RespError, RespFrame, RespLimits, RespProtocol,
+    RespStreamPart,
+};
+
+// RespFrameCodec 记住已经扫描到的位置以及所在的聚合类型层级，
+// 数据分多次到达时不会从头重新解析，每个字节只会被检查有限次
+#[derive(Debug, Default)]
+pub struct RespFrameCodec {
+    // scanned length of the current frame
+    pos: usize,
+    // bytes of the current header line already searched for CRLF
+    line_scanned: usize,
+    // open aggregates of the current frame
+    scanner: FrameScanner,
+    // replies are downgraded to RESP2 unless the client switched to RESP3
+    protocol: RespProtocol,
+}
+
+impl RespFrameCodec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_limits(limits: RespLimits) -> Self {
+        Self {
+            scanner: FrameScanner::new(limits),
+            ..Self::default()
+        }
+    }
+
+    pub fn protocol(&self) -> RespProtocol {
+        self.protocol
+    }
+
+    pub fn set_protocol(&mut self, protocol: RespProtocol) {
+        self.protocol = protocol;
+    }
+
+    // an inline command is a line that does not start with a RESP type prefix,
+    // e.g. "PING\r\n", it is turned into an array of bulk strings
+    fn decode_inline(&mut self, src: &mut BytesMut) -> Result<Option<RespFrame>> {
+        let Some(end) = src[self.line_scanned..]
+            .iter()
+            .position(|b| *b == b'\n')
+            .map(|i| self.line_scanned + i)
+        else {
+            // 和 redis 一样，inline 命令和帧头使用同样的长度限制
+            if src.len() > self.scanner.limits().max_line_len {
+                return Err(RespError::LimitExceeded("too big inline request".to_string()).into());
+            }
+            self.line_scanned = src.len();
+            return Ok(None);
+        };
+        self.line_scanned = 0;
+        let line = src.split_to(end + 1);
+        let args = split_args(&line).ok_or_else(|| {
+            RespError::InvalidFrame("unbalanced quotes in inline request".to_string())
+        })?;
+        let args: Vec<RespFrame> = args
+            .into_iter()
+            .map(|arg| BulkString::from(arg).into())
+            .collect();
+        Ok(Some(RespArray::new(args).into()))
+    }
+
+    // scan the buffer from the saved position, return the length of the frame once complete
+    fn scan(&mut self, src: &[u8]) -> Result<Option<usize>> {
+        loop {
+            let from = self.pos + self.line_scanned;
+            let Some(end) = find_crlf(&src[from..]).map(|i| from + i) else {
+                // the last byte may be the '\r' of a CRLF
+                self.line_scanned = (src.len() - self.pos).saturating_sub(1);
+                self.scanner.limits().check_line(self.line_scanned)?;
+                return Ok(None);
+            };
+            self.scanner.limits().check_line(end - self.pos)?;
+            let header_len = end + 2 - self.pos;
+            let header = self.scanner.header(&src[self.pos..end])?;
+            let mut len = header_len;
+            if let FrameHeader::Blob(blob_len) = header {
+                len += blob_len + 2;
+                if src.len() < self.pos + len {
+                    // no need to search the header again
+                    self.line_scanned = end - self.pos;
+                    return Ok(None);
+                }
+            }
+            self.pos += len;
+            self.line_scanned = 0;
+            if self.scanner.complete(header)? {
+                let len = self.pos;
+                self.pos = 0;
+                return Ok(Some(len));
+            }
+        }
+    }
+}
+
+impl Encoder<RespFrame> for RespFrameCodec {
+    type Error = anyhow::Error;
+
+    fn encode(&mut self, item: RespFrame, dst: &mut BytesMut) -> Result<()> {
+        let item = match self.protocol {
+            RespProtocol::Resp2 => item.into_resp2(),
+            RespProtocol::Resp3 => item,
+        };
+        dst.reserve(item.byte_size());
+        item.encode_to(dst);
+        Ok(())
+    }
+}
+
+// 分段发送大的回复，见 RespStreamPart。RESP2 客户端不能解析，直接报错
+impl Encoder<RespStreamPart> for RespFrameCodec {
+    type Error = anyhow::Error;
+
+    fn encode(&mut self, item: RespStreamPart, dst: &mut BytesMut) -> Result<()> {
+        if self.protocol == RespProtocol::Resp2 {
+            return Err(
+                RespError::InvalidFrame("streamed frames require RESP3".to_string()).into(),
+            );
+        }
+        item.encode_to(dst);
+        Ok(())
+    }
+}
+
+impl Decoder for RespFrameCodec {
+    type Item = RespFrame;
+    type Error = anyhow::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>> {
+        while self.pos == 0 && src.first().is_some_and(|b| !is_frame_prefix(*b)) {
+            match self.decode_inline(src)? {
+                // empty lines are ignored
+                Some(RespFrame::Array(args)) if args.is_empty() => continue,
+                frame => return Ok(frame),
+            }
+        }
+        match self.scan(src)? {
+            Some(len) => {
+                let mut data = src.split_to(len);
+                Ok(Some(RespFrame::decode_complete(&mut data)?))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{resp::OpenAggregate, RespMap, RespNull};
+
+    #[test]
+    fn test_decode_in_pieces() -> Result<()> {
+        let input = b"*2\r\n$3\r\nget\r\n*2\r\n+hello\r\n:+1\r\n$3\r\nget\r\n";
+        let mut codec = RespFrameCodec::new();
+        let mut buf = BytesMut::new();
+        let mut frames = vec![];
+        for b in input {
+            buf.extend_from_slice(&[*b]);
+            if let Some(frame) = codec.decode(&mut buf)? {
+                frames.push(frame);
+            }
+        }
+        assert!(buf.is_empty());
+        assert_eq!(
+            frames,
+            vec![
+                RespArray::new([
+                    BulkString::from("get").into(),
+                    RespArray::new(["hello".into(), 1.into()]).into(),
+                ])
+                .into(),
+                BulkString::from("get").into(),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_keeps_progress() -> Result<()> {
+        let mut codec = RespFrameCodec::new();
+        let mut buf = BytesMut::from("*3\r\n$3\r\nset\r\n$5\r\nhel");
+        assert_eq!(codec.decode(&mut buf)?, None);
+        assert_eq!(codec.pos, 13);
+        assert_eq!(codec.scanner.open, vec![OpenAggregate::Sized(2)]);
+
+        buf.extend_from_slice(b"lo\r\n*1\r\n+OK");
+        assert_eq!(codec.decode(&mut buf)?, None);
+        assert_eq!(codec.pos, 28);
+        assert_eq!(
+            codec.scanner.open,
+            vec![OpenAggregate::Sized(1), OpenAggregate::Sized(1)]
+        );
+
+        buf.extend_from_slice(b"\r\n");
+        let frame = codec.decode(&mut buf)?;
+        assert_eq!(
+            frame,
+            Some(
+                RespArray::new([
+                    BulkString::from("set").into(),
+                    BulkString::from("hello").into(),
+                    RespArray::new(["OK".into()]).into(),
+                ])
+                .into()
+            )
+        );
+        assert!(buf.is_empty());
+        assert_eq!(codec.pos, 0);
+        assert!(codec.scanner.open.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_limits() -> Result<()> {
+        let mut codec = RespFrameCodec::with_limits(RespLimits {
+            max_bulk_len: 1024,
+            max_aggregate_len: 4,
+            max_depth: 2,
+            ..RespLimits::default()
+        });
+        let mut buf = BytesMut::from("*9999999999\r\n");
+        assert!(codec.decode(&mut buf).is_err());
+
+        let mut codec = RespFrameCodec::with_limits(RespLimits {
+            max_bulk_len: 1024,
+            max_aggregate_len: 4,
+            max_depth: 2,
+            ..RespLimits::default()
+        });
+        let mut buf = BytesMut::from("*1\r\n*1\r\n*1\r\n");
+        let err = codec.decode(&mut buf).unwrap_err();
+        assert_eq!(
+            err.downcast::<RespError>()?,
+            RespError::LimitExceeded("too many nested aggregates".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_line_limit() -> Result<()> {
+        // a header without CRLF must not be buffered forever
+        for prefix in ["*", "$", "+", "-", ":", "*1\r\n+"] {
+            let mut codec = RespFrameCodec::new();
+            let mut buf = BytesMut::from(prefix);
+            let mut result = Ok(None);
+            for _ in 0..8 * 1024 {
+                buf.extend_from_slice(&[b'1'; 1024]);
+                result = codec.decode(&mut buf);
+                if result.is_err() {
+                    break;
+                }
+            }
+            assert_eq!(
+                result.unwrap_err().downcast::<RespError>()?,
+                RespError::LimitExceeded("too big header line".to_string()),
+                "{prefix:?}"
+            );
+            assert!(buf.len() <= 65 * 1024 + prefix.len());
+        }
+
+        let mut codec = RespFrameCodec::with_limits(RespLimits {
+            max_line_len: 4,
+            ..RespLimits::default()
+        });
+        let mut buf = BytesMut::from("+abc\r\n+abcd\r\n");
+        assert_eq!(codec.decode(&mut buf)?, Some("abc".into()));
+        assert!(codec.decode(&mut buf).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_empty_header() -> Result<()> {
+        let mut codec = RespFrameCodec::new();
+        let err = codec.decode(&mut BytesMut::from("*1\r\n\r\n")).unwrap_err();
+        assert_eq!(
+            err.downcast::<RespError>()?,
+            RespError::InvalidFrame("empty frame header".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_negative_length() {
+        for input in [
+            "%-1\r\n",
+            "~-1\r\n",
+            ">-1\r\n",
+            "!-1\r\n",
+            "=-1\r\n",
+            "*1\r\n%-1\r\n",
+        ] {
+            let mut codec = RespFrameCodec::new();
+            let err = codec.decode(&mut BytesMut::from(input)).unwrap_err();
+            assert!(
+                matches!(
+                    err.downcast_ref::<RespError>(),
+                    Some(RespError::InvalidFrameLength(-1))
+                ),
+                "{input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_with_larger_limits() -> Result<()> {
+        let mut codec = RespFrameCodec::with_limits(RespLimits {
+            max_depth: 200,
+            ..RespLimits::default()
+        });
+        let mut input = b"*1\r\n".repeat(150);
+        input.extend_from_slice(b"+OK\r\n");
+        let mut buf = BytesMut::from(&input[..]);
+        assert!(codec.decode(&mut buf)?.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_inline() -> Result<()> {
+        let mut codec = RespFrameCodec::new();
+        let mut buf = BytesMut::from("\r\nPING\r\nset k \"hello world\"\nGET");
+        assert_eq!(
+            codec.decode(&mut buf)?,
+            Some(RespArray::new([BulkString::from("PING").into()]).into())
+        );
+        assert_eq!(
+            codec.decode(&mut buf)?,
+            Some(
+                RespArray::new([
+                    BulkString::from("set").into(),
+                    BulkString::from("k").into(),
+                    BulkString::from("hello world").into(),
+                ])
+                .into()
+            )
+        );
+        assert_eq!(codec.decode(&mut buf)?, None);
+        buf.extend_from_slice(b" k\r\n*1\r\n$4\r\nPING\r\n");
+        assert_eq!(
+            codec.decode(&mut buf)?,
+            Some(
+                RespArray::new([BulkString::from("GET").into(), BulkString::from("k").into()])
+                    .into()
+            )
+        );
+        assert_eq!(
+            codec.decode(&mut buf)?,
+            Some(RespArray::new([BulkString::from("PING").into()]).into())
+        );
+
+        let mut buf = BytesMut::from("set k \"v\r\n");
+        assert!(codec.decode(&mut buf).is_err());
+
+        let mut codec = RespFrameCodec::new();
+        let mut buf = BytesMut::from(&b"a".repeat(64 * 1024 + 1)[..]);
+        assert!(codec.decode(&mut buf).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_invalid_frame() {
+        let mut codec = RespFrameCodec::new();
+        let mut buf = BytesMut::from("*1\r\n?abc\r\n");
+        assert!(codec.decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_encode_appends() -> Result<()> {
+        let mut codec = RespFrameCodec::new();
+        let mut dst = BytesMut::from("+OK\r\n");
+        let frame: RespFrame = RespArray::new([BulkString::from("a").into(), 1.into()]).into();
+        codec.encode(frame, &mut dst)?;
+        assert_eq!(dst, "+OK\r\n*2\r\n$1\r\na\r\n:+1\r\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_streamed_in_pieces() -> Result<()> {
+        let mut codec = RespFrameCodec::new();
+        let mut buf = BytesMut::new();
+        let data = b"*?\r\n$?\r\n;2\r\nab\r\n;1\r\nc\r\n;0\r\n%?\r\n+k\r\n:+1\r\n.\r\n.\r\n";
+        for (i, b) in data.iter().enumerate() {
+            buf.extend_from_slice(&[*b]);
+            let frame = codec.decode(&mut buf)?;
+            if i < data.len() - 1 {
+                assert_eq!(frame, None);
+            } else {
+                let map: RespMap = [("k", 1)].into_iter().collect();
+                let expected = RespArray::new([BulkString::from("abc").into(), map.into()]);
+                assert_eq!(frame, Some(expected.into()));
+            }
+        }
+        assert!(buf.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_streamed() -> Result<()> {
+        let mut codec = RespFrameCodec::new();
+        let mut dst = BytesMut::new();
+        assert!(codec.encode(RespStreamPart::ArrayStart, &mut dst).is_err());
+        assert!(dst.is_empty());
+
+        codec.set_protocol(RespProtocol::Resp3);
+        codec.encode(RespStreamPart::ArrayStart, &mut dst)?;
+        codec.encode(RespFrame::from(b"a"), &mut dst)?;
+        codec.encode(RespStreamPart::End, &mut dst)?;
+        assert_eq!(dst, "*?\r\n$1\r\na\r\n.\r\n");
+
+        let frame = codec.decode(&mut dst)?;
+        assert_eq!(frame, Some(RespArray::new([b"a".into()]).into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_protocol() -> Result<()> {
+        let mut codec = RespFrameCodec::new();
+        assert_eq!(codec.protocol(), RespProtocol::Resp2);
+        let mut dst = BytesMut::new();
+        codec.encode(RespFrame::from(RespNull), &mut dst)?;
+        assert_eq!(dst, "$-1\r\n");
+
+        codec.set_protocol(RespProtocol::Resp3);
+        let mut dst = BytesMut::new();
+        codec.encode(RespFrame::from(RespNull), &mut dst)?;
+        assert_eq!(dst, "_\r\n");
+        Ok(())
+    }
+}
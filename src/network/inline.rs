@@ -0,0 +1,134 @@
+// inline command: "<arg-1> <arg-2> ... <arg-n>\r\n"
+// 参数按空白分隔，引号规则和 redis 的 sdssplitargs 一致：
+// - 双引号内支持 \n \r \t \b \a \\ \" 以及 \xHH 转义
+// - 单引号内只支持 \' 转义
+// - 闭合的引号后面必须是空白或者行尾
+// 引号不匹配时返回 None
+pub(crate) fn split_args(line: &[u8]) -> Option<Vec<Vec<u8>>> {
+    let mut args = Vec::new();
+    let mut i = 0;
+    loop {
+        while i < line.len() && line[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i >= line.len() {
+            return Some(args);
+        }
+
+        let mut current = Vec::new();
+        let (mut in_dq, mut in_sq) = (false, false);
+        loop {
+            let c = line.get(i).copied();
+            let next = line.get(i + 1).copied();
+            if in_dq {
+                match (c?, next) {
+                    (b'\\', Some(b'x'))
+                        if i + 3 < line.len()
+                            && line[i + 2].is_ascii_hexdigit()
+                            && line[i + 3].is_ascii_hexdigit() =>
+                    {
+                        current.push(hex_value(line[i + 2]) << 4 | hex_value(line[i + 3]));
+                        i += 3;
+                    }
+                    (b'\\', Some(escaped)) => {
+                        current.push(match escaped {
+                            b'n' => b'\n',
+                            b'r' => b'\r',
+                            b't' => b'\t',
+                            b'b' => 0x08,
+                            b'a' => 0x07,
+                            other => other,
+                        });
+                        i += 1;
+                    }
+                    (b'"', next) => {
+                        if next.is_some_and(|b| !b.is_ascii_whitespace()) {
+                            return None;
+                        }
+                        i += 1;
+                        break;
+                    }
+                    (c, _) => current.push(c),
+                }
+            } else if in_sq {
+                match (c?, next) {
+                    (b'\\', Some(b'\'')) => {
+                        current.push(b'\'');
+                        i += 1;
+                    }
+                    (b'\'', next) => {
+                        if next.is_some_and(|b| !b.is_ascii_whitespace()) {
+                            return None;
+                        }
+                        i += 1;
+                        break;
+                    }
+                    (c, _) => current.push(c),
+                }
+            } else {
+                match c {
+                    None | Some(b' ' | b'\n' | b'\r' | b'\t' | b'\0') => break,
+                    Some(b'"') => in_dq = true,
+                    Some(b'\'') => in_sq = true,
+                    Some(c) => current.push(c),
+                }
+            }
+            i += 1;
+        }
+        args.push(current);
+    }
+}
+
+fn hex_value(b: u8) -> u8 {
+    match b {
+        b'0'..=b'9' => b - b'0',
+        b'a'..=b'f' => b - b'a' + 10,
+        _ => b - b'A' + 10,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(line: &str) -> Option<Vec<String>> {
+        split_args(line.as_bytes()).map(|args| {
+            args.into_iter()
+                .map(|a| String::from_utf8_lossy(&a).to_string())
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_split_args() {
+        assert_eq!(split("PING"), Some(vec!["PING".to_string()]));
+        assert_eq!(
+            split("  set  key\tvalue \r"),
+            Some(vec!["set".into(), "key".into(), "value".into()])
+        );
+        assert_eq!(split(""), Some(vec![]));
+        assert_eq!(
+            split(r#"set k "hello world\n" 'it\'s' "\x41\x4a""#),
+            Some(vec![
+                "set".into(),
+                "k".into(),
+                "hello world\n".into(),
+                "it's".into(),
+                "AJ".into()
+            ])
+        );
+        assert_eq!(
+            split(r#"set k """#),
+            Some(vec!["set".into(), "k".into(), "".into()])
+        );
+        assert_eq!(split(r#"a"b c""#), Some(vec!["ab c".into()]));
+    }
+
+    #[test]
+    fn test_split_args_unbalanced() {
+        assert_eq!(split(r#"set k "value"#), None);
+        assert_eq!(split("set k 'value"), None);
+        assert_eq!(split(r#"set k "value"x"#), None);
+        assert_eq!(split("set k 'value'x"), None);
+    }
+}
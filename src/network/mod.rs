@@ -1,26 +1,73 @@
+mod codec;
+mod inline;
+
 use anyhow::Result;
-use bytes::{Buf, BytesMut};
 use futures::SinkExt;
-use tokio::net::TcpStream;
+use tokio::{net::TcpStream, sync::mpsc};
 use tokio_stream::StreamExt;
-use tokio_util::codec::{Decoder, Encoder, Framed};
-use tracing::info;
+use tokio_util::codec::Framed;
+use tracing::{info, trace};
+
+use crate::{
+    Backend, Command, RespError, RespFrame, RespLimits, RespProtocol, RespPush, SimpleError,
+};
+
+pub use codec::RespFrameCodec;
 
-use crate::{Backend, Command, CommandExecutor, RespDecode, RespEncode, RespError, RespFrame};
+pub type PushSender = mpsc::UnboundedSender<RespPush>;
+pub type PushReceiver = mpsc::UnboundedReceiver<RespPush>;
+
+// limits 用于解码这个连接上的请求
+pub async fn stream_handler(stream: TcpStream, backend: Backend, limits: RespLimits) -> Result<()> {
+    let (_tx, rx) = mpsc::unbounded_channel();
+    stream_handler_with_push(stream, backend, limits, rx).await
+}
 
-pub async fn stream_handler(stream: TcpStream, backend: Backend) -> Result<()> {
-    let mut framed = Framed::new(stream, RespFrameCodec);
+// 除了回复请求，还会把 pushes 收到的服务端推送消息写到同一个连接上，
+// 推送消息只会出现在两个完整的回复之间。RESP2 下推送会变成数组，
+// 和回复无法区分，所以只有通过 HELLO 切换到 RESP3 之后才会转发
+pub async fn stream_handler_with_push(
+    stream: TcpStream,
+    backend: Backend,
+    limits: RespLimits,
+    mut pushes: PushReceiver,
+) -> Result<()> {
+    let mut framed = Framed::new(stream, RespFrameCodec::with_limits(limits));
     loop {
-        match framed.next().await {
+        let frame = tokio::select! {
+            frame = framed.next() => frame,
+            Some(push) = pushes.recv() => {
+                if framed.codec().protocol() == RespProtocol::Resp3 {
+                    framed.send(RespFrame::from(push)).await?;
+                } else {
+                    trace!("Dropped push on RESP2 connection: {push:?}");
+                }
+                continue;
+            }
+        };
+        match frame {
             Some(Ok(frame)) => {
                 let request = RedisRequest {
                     frame,
                     backend: backend.clone(),
+                    protocol: framed.codec().protocol(),
                 };
                 let response = request_handler(request).await?;
+                // HELLO 的回复已经使用新的协议
+                framed.codec_mut().set_protocol(response.protocol);
                 framed.send(response.frame).await?;
             }
-            Some(Err(e)) => return Err(e),
+            Some(Err(e)) => {
+                // 协议错误，回复错误后关闭连接
+                if let Some(err) = e.downcast_ref::<RespError>() {
+                    let msg = match err {
+                        RespError::LimitExceeded(_) => format!("ERR {err}"),
+                        _ => format!("ERR Protocol error: {err}"),
+                    };
+                    framed.send(RespFrame::from(SimpleError::new(msg))).await?;
+                }
+                return Err(e);
+            }
             None => return Ok(()),
         }
     }
@@ -29,45 +76,169 @@ pub async fn stream_handler(stream: TcpStream, backend: Backend) -> Result<()> {
 struct RedisRequest {
     frame: RespFrame,
     backend: Backend,
+    protocol: RespProtocol,
 }
 
 #[derive(Debug)]
 struct RedisResponse {
     frame: RespFrame,
+    // protocol of the connection from this response on
+    protocol: RespProtocol,
 }
 
-#[derive(Debug)]
-struct RespFrameCodec;
-
 async fn request_handler(request: RedisRequest) -> Result<RedisResponse> {
-    let (frame, backend) = (request.frame, request.backend);
-    let cmd = Command::try_from(frame)?;
+    let (frame, backend, protocol) = (request.frame, request.backend, request.protocol);
+    trace!("Received request:\n{}", frame.pretty());
+    let cmd = match Command::try_from(frame) {
+        Ok(cmd) => cmd,
+        Err(e) => {
+            // 命令错误回复给客户端，连接保持打开
+            info!("Invalid command: {e}");
+            let frame = RespFrame::from(e);
+            return Ok(RedisResponse { frame, protocol });
+        }
+    };
     info!("Executing command: {cmd:?}");
-    let frame = cmd.execute(&backend);
-    Ok(RedisResponse { frame })
+    let reply = cmd.execute_on(&backend, protocol);
+    trace!("Sending response:\n{}", reply.frame.pretty());
+    Ok(RedisResponse {
+        frame: reply.frame,
+        protocol: reply.protocol,
+    })
 }
 
-impl Encoder<RespFrame> for RespFrameCodec {
-    type Error = anyhow::Error;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, RespArray, RespNull, RespNullBulkString};
+    use tokio::net::TcpListener;
+
+    type TestClient = Framed<TcpStream, RespFrameCodec>;
+
+    // 启动只处理一个连接的服务端并连接上去，返回客户端和推送消息的发送端
+    async fn connect_test_server() -> Result<(TestClient, PushSender)> {
+        connect_test_server_with_limits(RespLimits::default()).await
+    }
+
+    async fn connect_test_server_with_limits(
+        limits: RespLimits,
+    ) -> Result<(TestClient, PushSender)> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await?;
+            stream_handler_with_push(stream, Backend::new(), limits, rx).await
+        });
+        let client = Framed::new(TcpStream::connect(addr).await?, RespFrameCodec::new());
+        Ok((client, tx))
+    }
+
+    #[tokio::test]
+    async fn test_push_between_replies() -> Result<()> {
+        let (mut client, tx) = connect_test_server().await?;
+        // 默认是 RESP2，推送消息需要 RESP3
+        let hello = RespArray::new([
+            BulkString::from("hello").into(),
+            BulkString::from("3").into(),
+        ]);
+        client.send(RespFrame::from(hello)).await?;
+        assert!(matches!(
+            client.next().await.transpose()?,
+            Some(RespFrame::Map(_))
+        ));
 
-    fn encode(&mut self, item: RespFrame, dst: &mut BytesMut) -> Result<()> {
-        dst.extend_from_slice(item.encode().as_slice());
+        let push = RespPush::new([
+            BulkString::from("message").into(),
+            BulkString::from("news").into(),
+        ]);
+        tx.send(push.clone())?;
+        assert_eq!(client.next().await.transpose()?, Some(push.into()));
+
+        let get = RespArray::new([BulkString::from("get").into(), BulkString::from("k").into()]);
+        client.send(RespFrame::from(get)).await?;
+        assert_eq!(client.next().await.transpose()?, Some(RespNull.into()));
         Ok(())
     }
-}
 
-impl Decoder for RespFrameCodec {
-    type Item = RespFrame;
-    type Error = anyhow::Error;
+    #[tokio::test]
+    async fn test_no_push_on_resp2() -> Result<()> {
+        let (mut client, tx) = connect_test_server().await?;
+        let push = RespPush::new([
+            BulkString::from("message").into(),
+            BulkString::from("news").into(),
+        ]);
+        tx.send(push)?;
 
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>> {
-        match RespFrame::decode(src.as_ref()) {
-            Ok(frame) => {
-                src.advance(frame.byte_size());
-                Ok(Some(frame))
-            }
-            Err(RespError::NotComplete) => Ok(None),
-            Err(e) => Err(e.into()),
-        }
+        // 下一个收到的必须是命令的回复
+        let get = RespArray::new([BulkString::from("get").into(), BulkString::from("k").into()]);
+        client.send(RespFrame::from(get)).await?;
+        assert_eq!(
+            client.next().await.transpose()?,
+            Some(RespNullBulkString.into())
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_command_error_keeps_connection() -> Result<()> {
+        let (mut client, _tx) = connect_test_server().await?;
+        let get = RespArray::new([BulkString::from("GET").into()]);
+        client.send(RespFrame::from(get)).await?;
+        assert_eq!(
+            client.next().await.transpose()?,
+            Some(SimpleError::new("ERR wrong number of arguments for 'get' command").into())
+        );
+
+        let get = RespArray::new([BulkString::from("get").into(), BulkString::from("k").into()]);
+        client.send(RespFrame::from(get)).await?;
+        assert_eq!(
+            client.next().await.transpose()?,
+            Some(RespNullBulkString.into())
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_server_limits() -> Result<()> {
+        let limits = RespLimits {
+            max_bulk_len: 4,
+            ..RespLimits::default()
+        };
+        let (mut client, _tx) = connect_test_server_with_limits(limits).await?;
+        let get = RespArray::new([
+            BulkString::from("get").into(),
+            BulkString::from("hello").into(),
+        ]);
+        client.send(RespFrame::from(get)).await?;
+        assert_eq!(
+            client.next().await.transpose()?,
+            Some(SimpleError::new("ERR Protocol error: invalid bulk length").into())
+        );
+        assert!(client.next().await.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_resp2_by_default() -> Result<()> {
+        let (mut client, _tx) = connect_test_server().await?;
+        let get = RespArray::new([BulkString::from("get").into(), BulkString::from("k").into()]);
+        client.send(RespFrame::from(get)).await?;
+        assert_eq!(
+            client.next().await.transpose()?,
+            Some(RespNullBulkString.into())
+        );
+
+        // HELLO 2 的回复是扁平的数组
+        let hello = RespArray::new([
+            BulkString::from("hello").into(),
+            BulkString::from("2").into(),
+        ]);
+        client.send(RespFrame::from(hello)).await?;
+        assert!(matches!(
+            client.next().await.transpose()?,
+            Some(RespFrame::Array(_))
+        ));
+        Ok(())
     }
 }
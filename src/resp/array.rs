@@ -1,8 +1,10 @@
 use std::ops::Deref;
 
-use crate::{RespDecode, RespEncode, RespFrame, RespResult};
+use bytes::{Buf, BufMut, BytesMut};
 
-use super::{read_len, CRLF};
+use crate::{RespDecode, RespEncode, RespError, RespFrame, RespLimits, RespResult};
+
+use super::{capacity_hint, frame_length, line_length, read_nullable_len, stream, CRLF};
 
 // array: "*<number-of-elements>\r\n<element-1>...<element-n>"
 //        "*2\r\n$3\r\nget\r\n$5\r\nhello\r\n"
@@ -10,28 +12,23 @@ use super::{read_len, CRLF};
 pub(crate) const PREFIX: u8 = b'*';
 pub(crate) const NULL: &[u8] = b"*-1\r\n";
 
-#[derive(Debug, Clone, PartialEq, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
 pub struct RespArray(Vec<RespFrame>);
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RespNullArray;
+
 impl RespEncode for RespArray {
-    fn encode(&self) -> Vec<u8> {
-        if self.is_empty() {
-            return NULL.to_vec();
-        }
-        let mut buf = Vec::with_capacity(self.byte_size());
-        buf.push(PREFIX);
-        buf.extend_from_slice(self.len().to_string().as_bytes());
-        buf.extend_from_slice(CRLF);
+    fn encode_to(&self, buf: &mut impl BufMut) {
+        buf.put_u8(PREFIX);
+        buf.put_slice(self.len().to_string().as_bytes());
+        buf.put_slice(CRLF);
         for frame in self.iter() {
-            buf.extend_from_slice(&frame.encode());
+            frame.encode_to(buf);
         }
-        buf
     }
 
     fn byte_size(&self) -> usize {
-        if self.is_empty() {
-            return NULL.len();
-        }
         let ct = self.len();
         let mut size = 1 + ct.to_string().len() + 2;
         for frame in self.iter() {
@@ -48,21 +45,72 @@ impl RespArray {
 }
 
 impl RespDecode for RespArray {
-    fn decode(buf: &[u8]) -> RespResult<Self> {
-        let (count, mut offset) = read_len(PREFIX, buf)?;
-        if count == -1 {
-            return Ok(RespArray::default());
+    fn decode(buf: &mut BytesMut) -> RespResult<Self> {
+        Self::expect_length(buf)?;
+        Self::decode_complete(buf)
+    }
+
+    fn expect_length(buf: &[u8]) -> RespResult<usize> {
+        frame_length(buf, &RespLimits::default())
+    }
+}
+
+impl RespArray {
+    pub(crate) fn decode_complete(buf: &mut BytesMut) -> RespResult<Self> {
+        if stream::is_streamed(buf) {
+            return stream::decode_elements(buf).map(RespArray);
         }
-        let mut frames = Vec::with_capacity(count as usize);
+        let (Some(count), offset) = read_nullable_len(buf)? else {
+            return Err(RespError::InvalidFrame(
+                "Null array is not an array".to_string(),
+            ));
+        };
+        buf.advance(offset);
+        let mut frames = Vec::with_capacity(capacity_hint(count, buf));
         for _ in 0..count {
-            let frame = RespFrame::decode(&buf[offset..])?;
-            offset += frame.byte_size();
-            frames.push(frame);
+            frames.push(RespFrame::decode_complete(buf)?);
         }
         Ok(RespArray(frames))
     }
 }
 
+impl RespEncode for RespNullArray {
+    fn encode_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(NULL);
+    }
+
+    fn byte_size(&self) -> usize {
+        NULL.len()
+    }
+}
+
+impl RespDecode for RespNullArray {
+    fn decode(buf: &mut BytesMut) -> RespResult<Self> {
+        let len = Self::expect_length(buf)?;
+        if !buf.starts_with(NULL) {
+            return Err(RespError::InvalidFrame(format!(
+                "Invalid null array: {:?}",
+                &buf[..len]
+            )));
+        }
+        buf.advance(len);
+        Ok(RespNullArray)
+    }
+
+    fn expect_length(buf: &[u8]) -> RespResult<usize> {
+        line_length(buf)
+    }
+}
+
+impl IntoIterator for RespArray {
+    type Item = RespFrame;
+    type IntoIter = std::vec::IntoIter<RespFrame>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
 impl Deref for RespArray {
     type Target = Vec<RespFrame>;
 
@@ -90,8 +138,9 @@ mod tests {
         assert_eq!(frame.byte_size(), buf.len());
 
         let frame: RespFrame = RespArray::new(vec![]).into();
-        assert_eq!(frame.encode(), NULL);
-        let frame: RespFrame = RespArray::default().into();
+        assert_eq!(frame.encode(), b"*0\r\n");
+        assert_eq!(frame.byte_size(), 4);
+        let frame: RespFrame = RespNullArray.into();
         assert_eq!(frame.encode(), NULL);
         assert_eq!(NULL.len(), frame.byte_size());
     }
@@ -99,15 +148,29 @@ mod tests {
     #[test]
     fn test_array_decode() -> Result<()> {
         let buf = b"*2\r\n$3\r\nset\r\n$5\r\nhello\r\n";
-        let frame = RespArray::decode(buf)?;
+        let frame = RespArray::decode(&mut BytesMut::from(&buf[..]))?;
         assert_eq!(frame, RespArray::new([b"set".into(), b"hello".into()]));
         assert_eq!(frame.byte_size(), buf.len());
 
-        let ret = RespArray::decode(b"*2\r\n$3\r\nset\r\n");
+        let mut buf = BytesMut::from("*2\r\n$3\r\nset\r\n");
+        let ret = RespArray::decode(&mut buf);
         assert_eq!(ret.unwrap_err(), RespError::NotComplete);
+        assert_eq!(buf, "*2\r\n$3\r\nset\r\n");
+
+        buf.extend_from_slice(b"$5\r\nhello\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert_eq!(frame, RespArray::new([b"set".into(), b"hello".into()]));
+        assert!(buf.is_empty());
+
+        let frame = RespFrame::decode(&mut BytesMut::from(NULL))?;
+        assert_eq!(frame, RespNullArray.into());
+
+        let frame = RespFrame::decode(&mut BytesMut::from("*0\r\n"))?;
+        assert_eq!(frame, RespArray::new([]).into());
+        assert_eq!(frame.encode(), b"*0\r\n");
 
-        let frame = RespFrame::decode(NULL)?;
-        assert_eq!(frame, RespArray::default().into());
+        let ret = RespArray::decode(&mut BytesMut::from(NULL));
+        assert!(matches!(ret, Err(RespError::InvalidFrame(_))));
 
         Ok(())
     }
@@ -0,0 +1,132 @@
+use bytes::{Buf, BufMut, BytesMut};
+
+use super::{frame_length, read_len, CRLF};
+use crate::{RespDecode, RespEncode, RespFrame, RespLimits, RespMap, RespResult};
+
+// attribute: "|<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n><reply>"
+// 属性是附加在紧随其后的回复上的辅助信息，这里和回复一起作为一个帧处理
+pub(crate) const PREFIX: u8 = b'|';
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RespAttribute {
+    attributes: RespMap,
+    frame: Box<RespFrame>,
+}
+
+impl RespAttribute {
+    pub fn new(attributes: RespMap, frame: impl Into<RespFrame>) -> Self {
+        Self {
+            attributes,
+            frame: Box::new(frame.into()),
+        }
+    }
+
+    pub fn attributes(&self) -> &RespMap {
+        &self.attributes
+    }
+
+    // the reply the attributes are attached to
+    pub fn frame(&self) -> &RespFrame {
+        &self.frame
+    }
+
+    pub fn into_parts(self) -> (RespMap, RespFrame) {
+        (self.attributes, *self.frame)
+    }
+}
+
+impl RespEncode for RespAttribute {
+    fn encode_to(&self, buf: &mut impl BufMut) {
+        buf.put_u8(PREFIX);
+        buf.put_slice(self.attributes.len().to_string().as_bytes());
+        buf.put_slice(CRLF);
+        self.attributes.encode_entries(buf);
+        self.frame.encode_to(buf);
+    }
+
+    fn byte_size(&self) -> usize {
+        self.attributes.len().to_string().len()
+            + 3
+            + self.attributes.entries_byte_size()
+            + self.frame.byte_size()
+    }
+}
+
+impl RespDecode for RespAttribute {
+    fn decode(buf: &mut BytesMut) -> RespResult<Self> {
+        Self::expect_length(buf)?;
+        Self::decode_complete(buf)
+    }
+
+    fn expect_length(buf: &[u8]) -> RespResult<usize> {
+        frame_length(buf, &RespLimits::default())
+    }
+}
+
+impl RespAttribute {
+    pub(crate) fn decode_complete(buf: &mut BytesMut) -> RespResult<Self> {
+        let (num, offset) = read_len(PREFIX, buf)?;
+        buf.advance(offset);
+        let attributes = RespMap::decode_entries(buf, num)?;
+        let frame = RespFrame::decode_complete(buf)?;
+        Ok(Self::new(attributes, frame))
+    }
+}
+
+impl RespFrame {
+    // attach attributes to this reply
+    pub fn with_attributes(self, attributes: RespMap) -> RespFrame {
+        RespAttribute::new(attributes, self).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, RespArray, RespError};
+    use anyhow::Result;
+
+    const BUF: &[u8] = b"|1\r\n+key-popularity\r\n%2\r\n+a\r\n,+0.1923\r\n+b\r\n,+0.0012\r\n*2\r\n:+2039123\r\n:+9543892\r\n";
+
+    fn attribute() -> RespAttribute {
+        let mut popularity = RespMap::new();
+        popularity.insert("a".into(), 0.1923.into());
+        popularity.insert("b".into(), 0.0012.into());
+        let mut attributes = RespMap::new();
+        attributes.insert("key-popularity".into(), popularity.into());
+        RespAttribute::new(attributes, RespArray::new([2039123.into(), 9543892.into()]))
+    }
+
+    #[test]
+    fn test_attribute_encode() {
+        let frame: RespFrame = attribute().into();
+        assert_eq!(frame.encode(), BUF);
+        assert_eq!(frame.byte_size(), BUF.len());
+
+        let mut ttl = RespMap::new();
+        ttl.insert("ttl".into(), 100.into());
+        let frame = RespFrame::from(BulkString::from("v")).with_attributes(ttl);
+        assert_eq!(frame.encode(), b"|1\r\n+ttl\r\n:+100\r\n$1\r\nv\r\n");
+    }
+
+    #[test]
+    fn test_attribute_decode() -> Result<()> {
+        let mut buf = BytesMut::from(BUF);
+        buf.extend_from_slice(b"+OK\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(frame, attribute().into());
+        assert_eq!(buf, "+OK\r\n");
+
+        let (attributes, reply) = attribute().into_parts();
+        assert_eq!(attributes.len(), 1);
+        assert_eq!(
+            reply,
+            RespArray::new([2039123.into(), 9543892.into()]).into()
+        );
+
+        // the reply is part of the frame
+        let mut buf = BytesMut::from(&BUF[..BUF.len() - 11]);
+        assert_eq!(RespAttribute::decode(&mut buf), Err(RespError::NotComplete));
+        Ok(())
+    }
+}
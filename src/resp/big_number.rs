@@ -0,0 +1,217 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display, Formatter},
+    ops::Deref,
+    str::{from_utf8, FromStr},
+};
+
+use bytes::{Buf, BufMut, BytesMut};
+
+use super::{line_length, CRLF};
+use crate::{RespDecode, RespEncode, RespError, RespResult};
+
+// big number: "([+|-]<number>\r\n"
+pub(crate) const PREFIX: u8 = b'(';
+
+// 任意精度整数，保存为规范化后的十进制字符串：没有 '+' 号和多余的前导 0，0 没有符号
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RespBigNumber(String);
+
+impl RespBigNumber {
+    pub fn is_negative(&self) -> bool {
+        self.0.starts_with('-')
+    }
+
+    // (number of digits, digits) without the sign
+    fn magnitude(&self) -> (usize, &str) {
+        let digits = self.0.trim_start_matches('-');
+        (digits.len(), digits)
+    }
+}
+
+impl RespEncode for RespBigNumber {
+    fn encode_to(&self, buf: &mut impl BufMut) {
+        buf.put_u8(PREFIX);
+        buf.put_slice(self.0.as_bytes());
+        buf.put_slice(CRLF);
+    }
+
+    fn byte_size(&self) -> usize {
+        self.0.len() + 3
+    }
+}
+
+impl RespDecode for RespBigNumber {
+    fn decode(buf: &mut BytesMut) -> RespResult<Self> {
+        let len = Self::expect_length(buf)?;
+        let number = from_utf8(&buf[1..len - CRLF.len()])?.parse()?;
+        buf.advance(len);
+        Ok(number)
+    }
+
+    fn expect_length(buf: &[u8]) -> RespResult<usize> {
+        line_length(buf)
+    }
+}
+
+impl FromStr for RespBigNumber {
+    type Err = RespError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(RespError::InvalidFrame(format!("Invalid big number: {s}")));
+        }
+        let digits = digits.trim_start_matches('0');
+        let number = match (digits.is_empty(), negative) {
+            (true, _) => "0".to_string(),
+            (false, true) => format!("-{digits}"),
+            (false, false) => digits.to_string(),
+        };
+        Ok(Self(number))
+    }
+}
+
+impl TryFrom<&str> for RespBigNumber {
+    type Error = RespError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<i128> for RespBigNumber {
+    fn from(n: i128) -> Self {
+        Self(n.to_string())
+    }
+}
+
+impl From<i64> for RespBigNumber {
+    fn from(n: i64) -> Self {
+        Self(n.to_string())
+    }
+}
+
+impl TryFrom<&RespBigNumber> for i128 {
+    type Error = RespError;
+
+    fn try_from(n: &RespBigNumber) -> Result<Self, Self::Error> {
+        Ok(n.0.parse()?)
+    }
+}
+
+impl TryFrom<&RespBigNumber> for i64 {
+    type Error = RespError;
+
+    fn try_from(n: &RespBigNumber) -> Result<Self, Self::Error> {
+        Ok(n.0.parse()?)
+    }
+}
+
+// 按数值大小排序：先比符号，再比位数，位数相同时按字典序
+impl PartialOrd for RespBigNumber {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RespBigNumber {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.is_negative(), other.is_negative()) {
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => self.magnitude().cmp(&other.magnitude()),
+            (true, true) => other.magnitude().cmp(&self.magnitude()),
+        }
+    }
+}
+
+impl Display for RespBigNumber {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Deref for RespBigNumber {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RespFrame;
+    use anyhow::Result;
+
+    #[test]
+    fn test_big_number_encode() -> Result<()> {
+        let frame: RespFrame = RespBigNumber::from(i128::MAX).into();
+        assert_eq!(
+            frame.encode(),
+            b"(170141183460469231731687303715884105727\r\n"
+        );
+        assert_eq!(frame.byte_size(), 42);
+
+        let n: RespBigNumber = "-3492890328409238509324850943850943825024385".parse()?;
+        assert_eq!(
+            n.encode(),
+            b"(-3492890328409238509324850943850943825024385\r\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_big_number_decode() -> Result<()> {
+        let mut buf = BytesMut::from("(3492890328409238509324850943850943825024385\r\nextra");
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(
+            frame,
+            RespBigNumber::try_from("3492890328409238509324850943850943825024385")?.into()
+        );
+        assert_eq!(buf, "extra");
+
+        let n = RespBigNumber::decode(&mut BytesMut::from("(+00123\r\n"))?;
+        assert_eq!(&*n, "123");
+        assert_eq!(i128::try_from(&n)?, 123);
+
+        let n = RespBigNumber::decode(&mut BytesMut::from("(-0\r\n"))?;
+        assert_eq!(&*n, "0");
+
+        assert_eq!(
+            RespBigNumber::decode(&mut BytesMut::from("(12")),
+            Err(RespError::NotComplete)
+        );
+        assert!(RespBigNumber::decode(&mut BytesMut::from("(12a\r\n")).is_err());
+        assert!(RespBigNumber::decode(&mut BytesMut::from("(-\r\n")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_big_number_convert() -> Result<()> {
+        let n = RespBigNumber::from(-42i64);
+        assert!(n.is_negative());
+        assert_eq!(i64::try_from(&n)?, -42);
+
+        let n: RespBigNumber = "99999999999999999999999999999999999999999".parse()?;
+        assert!(i128::try_from(&n).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_big_number_ord() -> Result<()> {
+        let n = |s: &str| s.parse::<RespBigNumber>();
+        assert!(n("9")? < n("10")?);
+        assert!(n("-10")? < n("-9")?);
+        assert!(n("-1")? < n("0")?);
+        assert!(n("123")? < n("124")?);
+        assert_eq!(n("+007")?.cmp(&n("7")?), Ordering::Equal);
+        Ok(())
+    }
+}
@@ -1,3 +1,5 @@
+use bytes::{Buf, BufMut, BytesMut};
+
 use super::{FALSE, TRUE};
 use crate::{RespDecode, RespEncode, RespError, RespResult};
 
@@ -6,12 +8,8 @@ pub(crate) const PREFIX: u8 = b'#';
 const BYTE_SIZE: usize = 4;
 
 impl RespEncode for bool {
-    fn encode(&self) -> Vec<u8> {
-        if *self {
-            TRUE.to_vec()
-        } else {
-            FALSE.to_vec()
-        }
+    fn encode_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(if *self { TRUE } else { FALSE });
     }
 
     fn byte_size(&self) -> usize {
@@ -20,14 +18,14 @@ impl RespEncode for bool {
 }
 
 impl RespDecode for bool {
-    fn decode(buf: &[u8]) -> RespResult<Self> {
-        if buf.len() < BYTE_SIZE {
-            return Err(RespError::NotComplete);
-        }
+    fn decode(buf: &mut BytesMut) -> RespResult<Self> {
+        let len = Self::expect_length(buf)?;
 
         if buf.starts_with(TRUE) {
+            buf.advance(len);
             Ok(true)
         } else if buf.starts_with(FALSE) {
+            buf.advance(len);
             Ok(false)
         } else {
             Err(RespError::InvalidFrame(format!(
@@ -36,6 +34,13 @@ impl RespDecode for bool {
             )))
         }
     }
+
+    fn expect_length(buf: &[u8]) -> RespResult<usize> {
+        if buf.len() < BYTE_SIZE {
+            return Err(RespError::NotComplete);
+        }
+        Ok(BYTE_SIZE)
+    }
 }
 
 #[cfg(test)]
@@ -56,7 +61,11 @@ mod tests {
 
     #[test]
     fn test_decode() {
-        assert!(bool::decode(TRUE).unwrap());
-        assert!(!bool::decode(FALSE).unwrap());
+        assert!(bool::decode(&mut BytesMut::from(TRUE)).unwrap());
+        assert!(!bool::decode(&mut BytesMut::from(FALSE)).unwrap());
+        assert_eq!(
+            bool::decode(&mut BytesMut::from("#t")),
+            Err(RespError::NotComplete)
+        );
     }
 }
@@ -1,27 +1,27 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
 use super::{read_len, CRLF};
 use crate::{RespDecode, RespEncode, RespError, RespResult};
 
 // bulk error: "!<length>\r\n<error>\r\n"
 pub(crate) const PREFIX: u8 = b'!';
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct BulkError(Vec<u8>);
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BulkError(Bytes);
 
 impl BulkError {
-    pub fn new(data: impl Into<Vec<u8>>) -> Self {
+    pub fn new(data: impl Into<Bytes>) -> Self {
         BulkError(data.into())
     }
 }
 
 impl RespEncode for BulkError {
-    fn encode(&self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(self.byte_size());
-        buf.push(PREFIX);
-        buf.extend_from_slice(self.0.len().to_string().as_bytes());
-        buf.extend_from_slice(CRLF);
-        buf.extend_from_slice(self.as_ref());
-        buf.extend_from_slice(CRLF);
-        buf
+    fn encode_to(&self, buf: &mut impl BufMut) {
+        buf.put_u8(PREFIX);
+        buf.put_slice(self.0.len().to_string().as_bytes());
+        buf.put_slice(CRLF);
+        buf.put_slice(self.as_ref());
+        buf.put_slice(CRLF);
     }
 
     fn byte_size(&self) -> usize {
@@ -30,19 +30,28 @@ impl RespEncode for BulkError {
 }
 
 impl RespDecode for BulkError {
-    fn decode(buf: &[u8]) -> RespResult<Self> {
+    fn decode(buf: &mut BytesMut) -> RespResult<Self> {
         let (len, offset) = read_len(PREFIX, buf)?;
-        let total_len = len as usize + offset + 2;
-        if buf.len() < total_len {
-            return Err(RespError::NotComplete);
-        }
+        let total_len = Self::expect_length(buf)?;
         if buf[total_len - 2] != CRLF[0] || buf[total_len - 1] != CRLF[1] {
             return Err(RespError::InvalidFrame(format!(
                 "Invalid bulk error tail: {:?}",
                 &buf[total_len - 2..total_len]
             )));
         }
-        Ok(BulkError(buf[offset..total_len - 2].to_vec()))
+        let mut data = buf.split_to(total_len);
+        data.advance(offset);
+        data.truncate(len);
+        Ok(BulkError(data.freeze()))
+    }
+
+    fn expect_length(buf: &[u8]) -> RespResult<usize> {
+        let (len, offset) = read_len(PREFIX, buf)?;
+        let total_len = len + offset + 2;
+        if buf.len() < total_len {
+            return Err(RespError::NotComplete);
+        }
+        Ok(total_len)
     }
 }
 
@@ -59,32 +68,32 @@ mod tests {
 
     #[test]
     fn test_encode() {
-        let frame: RespFrame = BulkError::new(b"bulk error").into();
+        let frame: RespFrame = BulkError::new("bulk error").into();
         assert_eq!(frame.encode(), b"!10\r\nbulk error\r\n");
     }
 
     #[test]
     fn test_byte_size() {
-        let frame: RespFrame = BulkError::new(b"bulk error").into();
+        let frame: RespFrame = BulkError::new("bulk error").into();
         assert_eq!(frame.byte_size(), 17);
     }
 
     #[test]
     fn test_decode() {
         assert_eq!(
-            RespFrame::decode(b"!10\r\nbulk error\r\n"),
-            Ok(BulkError::new(b"bulk error").into()),
+            RespFrame::decode(&mut BytesMut::from("!10\r\nbulk error\r\n")),
+            Ok(BulkError::new("bulk error").into()),
         );
         assert_eq!(
-            RespFrame::decode(b"!10\r\nbulk error"),
+            RespFrame::decode(&mut BytesMut::from("!10\r\nbulk error")),
             Err(RespError::NotComplete),
         );
         assert_eq!(
-            RespFrame::decode(b"!10\r\nbulk error\r\nextra"),
-            Ok(BulkError::new(b"bulk error").into()),
+            RespFrame::decode(&mut BytesMut::from("!10\r\nbulk error\r\nextra")),
+            Ok(BulkError::new("bulk error").into()),
         );
         assert_eq!(
-            RespFrame::decode(b"!10\r\nbulk error\n\nextra"),
+            RespFrame::decode(&mut BytesMut::from("!10\r\nbulk error\n\nextra")),
             Err(RespError::InvalidFrame(
                 "Invalid bulk error tail: [10, 10]".to_string()
             )),
@@ -3,73 +3,119 @@ use std::{
     ops::Deref,
 };
 
-use super::CRLF;
-use crate::{resp::read_len, RespDecode, RespEncode, RespError, RespResult};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use super::{frame_length, line_length, stream, CRLF};
+use crate::{resp::read_nullable_len, RespDecode, RespEncode, RespError, RespLimits, RespResult};
 
 pub(crate) const PREFIX: u8 = b'$';
 pub(crate) const NULL: &[u8] = b"$-1\r\n";
 
-//bulk string: "$<length>\r\n<data>\r\n"
+//bulk string: "$<length>\r\n<data>\r\n", a streamed string is decoded into one bulk string
 //null bulk string: "$-1\r\n"
-#[derive(Debug, Clone, PartialEq, Default)]
-pub struct BulkString(pub(crate) Vec<u8>);
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct BulkString(pub(crate) Bytes);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RespNullBulkString;
+
 impl BulkString {
-    pub fn new(data: impl Into<Vec<u8>>) -> Self {
+    pub fn new(data: impl Into<Bytes>) -> Self {
         BulkString(data.into())
     }
 }
 
 impl RespEncode for BulkString {
-    fn encode(&self) -> Vec<u8> {
-        if self.is_empty() {
-            return NULL.to_vec();
-        }
-        let mut buf = Vec::with_capacity(self.byte_size());
-        buf.push(PREFIX);
-        buf.extend_from_slice(self.0.len().to_string().as_bytes());
-        buf.extend_from_slice(CRLF);
-        buf.extend_from_slice(self.as_ref());
-        buf.extend_from_slice(CRLF);
-        buf
+    fn encode_to(&self, buf: &mut impl BufMut) {
+        buf.put_u8(PREFIX);
+        buf.put_slice(self.0.len().to_string().as_bytes());
+        buf.put_slice(CRLF);
+        buf.put_slice(self.as_ref());
+        buf.put_slice(CRLF);
     }
 
     fn byte_size(&self) -> usize {
-        if self.is_empty() {
-            return NULL.len();
-        }
         self.len() + self.len().to_string().len() + 5
     }
 }
 
 impl RespDecode for BulkString {
-    fn decode(buf: &[u8]) -> RespResult<Self> {
-        let (len, offset) = read_len(PREFIX, buf)?;
-        if len == -1 {
-            return Ok(BulkString::default());
-        }
-        let total_len = len as usize + offset + 2;
-        if buf.len() < total_len {
-            return Err(RespError::NotComplete);
+    fn decode(buf: &mut BytesMut) -> RespResult<Self> {
+        if stream::is_streamed(buf) {
+            Self::expect_length(buf)?;
+            return stream::decode_chunks(buf).map(BulkString);
         }
+        let (Some(len), offset) = read_nullable_len(buf)? else {
+            return Err(RespError::InvalidFrame(
+                "Null bulk string is not a bulk string".to_string(),
+            ));
+        };
+        let total_len = Self::expect_length(buf)?;
         if buf[total_len - 2] != CRLF[0] || buf[total_len - 1] != CRLF[1] {
             return Err(RespError::InvalidFrame(format!(
                 "Invalid bulk string tail: {:?}",
                 &buf[total_len - 2..total_len]
             )));
         }
-        Ok(BulkString(buf[offset..total_len - 2].to_vec()))
+        let mut data = buf.split_to(total_len);
+        data.advance(offset);
+        data.truncate(len);
+        Ok(BulkString(data.freeze()))
+    }
+
+    fn expect_length(buf: &[u8]) -> RespResult<usize> {
+        if stream::is_streamed(buf) {
+            return frame_length(buf, &RespLimits::default());
+        }
+        let (len, offset) = read_nullable_len(buf)?;
+        let Some(len) = len else {
+            return Ok(offset);
+        };
+        let total_len = len + offset + 2;
+        if buf.len() < total_len {
+            return Err(RespError::NotComplete);
+        }
+        Ok(total_len)
+    }
+}
+
+impl RespEncode for RespNullBulkString {
+    fn encode_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(NULL);
+    }
+
+    fn byte_size(&self) -> usize {
+        NULL.len()
+    }
+}
+
+impl RespDecode for RespNullBulkString {
+    fn decode(buf: &mut BytesMut) -> RespResult<Self> {
+        let len = Self::expect_length(buf)?;
+        if !buf.starts_with(NULL) {
+            return Err(RespError::InvalidFrame(format!(
+                "Invalid null bulk string: {:?}",
+                &buf[..len]
+            )));
+        }
+        buf.advance(len);
+        Ok(RespNullBulkString)
+    }
+
+    fn expect_length(buf: &[u8]) -> RespResult<usize> {
+        line_length(buf)
     }
 }
 
 impl From<&[u8]> for BulkString {
     fn from(s: &[u8]) -> Self {
-        BulkString::new(s)
+        BulkString::new(Bytes::copy_from_slice(s))
     }
 }
 
 impl From<&str> for BulkString {
     fn from(s: &str) -> Self {
-        BulkString::new(s.as_bytes())
+        BulkString::from(s.as_bytes())
     }
 }
 
@@ -85,9 +131,21 @@ impl From<String> for BulkString {
     }
 }
 
+impl From<Vec<u8>> for BulkString {
+    fn from(s: Vec<u8>) -> Self {
+        BulkString::new(s)
+    }
+}
+
+impl From<Bytes> for BulkString {
+    fn from(s: Bytes) -> Self {
+        BulkString::new(s)
+    }
+}
+
 impl<const N: usize> From<&[u8; N]> for BulkString {
     fn from(s: &[u8; N]) -> Self {
-        BulkString::new(s)
+        BulkString::from(&s[..])
     }
 }
 
@@ -98,7 +156,7 @@ impl AsRef<[u8]> for BulkString {
 }
 
 impl Deref for BulkString {
-    type Target = Vec<u8>;
+    type Target = Bytes;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -113,45 +171,77 @@ mod tests {
 
     #[test]
     fn test_encode() {
-        let frame: RespFrame = BulkString::new(b"bulk string").into();
+        let frame: RespFrame = BulkString::from(b"bulk string").into();
         assert_eq!(frame.encode(), b"$11\r\nbulk string\r\n");
 
         let frame: RespFrame = BulkString::default().into();
+        assert_eq!(frame.encode(), b"$0\r\n\r\n");
+        assert_eq!(6, frame.byte_size());
+
+        let frame: RespFrame = RespNullBulkString.into();
         assert_eq!(frame.encode(), NULL);
         assert_eq!(5, frame.byte_size());
     }
 
     #[test]
     fn test_byte_size() {
-        let frame: RespFrame = BulkString::new(b"bulk string").into();
+        let frame: RespFrame = BulkString::from(b"bulk string").into();
         assert_eq!(frame.byte_size(), 18);
     }
 
     #[test]
     fn test_decode() {
+        let mut buf = BytesMut::from("$11\r\nbulk string\r\n");
         assert_eq!(
-            RespFrame::decode(b"$11\r\nbulk string\r\n"),
-            Ok(BulkString::new(b"bulk string").into()),
-        );
-        assert_eq!(
-            RespFrame::decode(b"$11\r\nbulk string\r\nabc"),
-            Ok(BulkString::new(b"bulk string").into()),
-        );
-        assert_eq!(
-            RespFrame::decode(b"$11\r\nbulk string"),
-            Err(RespError::NotComplete),
+            RespFrame::decode(&mut buf),
+            Ok(BulkString::from(b"bulk string").into()),
         );
+        assert!(buf.is_empty());
+
+        let mut buf = BytesMut::from("$11\r\nbulk string\r\nabc");
         assert_eq!(
-            RespFrame::decode(b"$11\r\nbulk string\r"),
-            Err(RespError::NotComplete),
+            RespFrame::decode(&mut buf),
+            Ok(BulkString::from(b"bulk string").into()),
         );
+        assert_eq!(buf, "abc");
+
+        let mut buf = BytesMut::from("$11\r\nbulk string");
+        assert_eq!(RespFrame::decode(&mut buf), Err(RespError::NotComplete));
+        assert_eq!(buf.len(), 16);
+
+        let mut buf = BytesMut::from("$11\r\nbulk string\r");
+        assert_eq!(RespFrame::decode(&mut buf), Err(RespError::NotComplete));
+
+        let mut buf = BytesMut::from("$11\r\nbulk string\n\nextra");
         assert_eq!(
-            RespFrame::decode(b"$11\r\nbulk string\n\nextra"),
+            RespFrame::decode(&mut buf),
             Err(RespError::InvalidFrame(
                 "Invalid bulk string tail: [10, 10]".to_string()
             )),
         );
 
-        assert_eq!(RespFrame::decode(NULL), Ok(BulkString::default().into()),);
+        let mut buf = BytesMut::from(NULL);
+        assert_eq!(RespFrame::decode(&mut buf), Ok(RespNullBulkString.into()));
+        assert!(buf.is_empty());
+
+        let mut buf = BytesMut::from("$0\r\n\r\n");
+        assert_eq!(
+            RespFrame::decode(&mut buf),
+            Ok(BulkString::default().into())
+        );
+        assert!(buf.is_empty());
+
+        let mut buf = BytesMut::from("$-1\r");
+        assert_eq!(RespFrame::decode(&mut buf), Err(RespError::NotComplete));
+    }
+
+    #[test]
+    fn test_decode_zero_copy() -> RespResult<()> {
+        let mut buf = BytesMut::from("$5\r\nhello\r\n");
+        let data_ptr = buf[4..].as_ptr();
+        let s = BulkString::decode(&mut buf)?;
+        assert_eq!(s.as_ref(), b"hello");
+        assert_eq!(s.as_ptr(), data_ptr);
+        Ok(())
     }
 }
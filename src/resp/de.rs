@@ -0,0 +1,381 @@
+use serde::de::{
+    self,
+    value::{MapDeserializer, SeqDeserializer},
+    DeserializeOwned, IntoDeserializer, Visitor,
+};
+use std::{fmt::Display, str::from_utf8};
+
+use crate::{RespError, RespFrame};
+
+// 把 RespFrame 转换成 rust 类型，是 to_frame 的逆操作。
+// 另外为了能直接读取 hash 里的数据：
+// - 数字和 bool 也可以从字符串解析，例如 "$2\r\n42\r\n" -> 42
+// - struct/map 也可以从 key、value 交替排列的数组读取（RESP2 的 HGETALL 回复）
+pub fn from_frame<T: DeserializeOwned>(frame: RespFrame) -> Result<T, RespError> {
+    T::deserialize(frame)
+}
+
+impl de::Error for RespError {
+    fn custom<T: Display>(msg: T) -> Self {
+        RespError::Message(msg.to_string())
+    }
+}
+
+impl RespFrame {
+    // the text of a string frame
+    fn as_text(&self) -> Option<Result<&str, RespError>> {
+        let bytes: &[u8] = match self {
+            RespFrame::BulkString(s) => s,
+            RespFrame::SimpleString(s) => s,
+            RespFrame::VerbatimString(s) => s.data(),
+            _ => return None,
+        };
+        Some(from_utf8(bytes).map_err(RespError::from))
+    }
+
+    fn error_message(&self) -> Option<String> {
+        match self {
+            RespFrame::SimpleError(e) => Some(String::from_utf8_lossy(e.as_ref()).into_owned()),
+            RespFrame::BulkError(e) => Some(String::from_utf8_lossy(e.as_ref()).into_owned()),
+            _ => None,
+        }
+    }
+
+    fn is_null(&self) -> bool {
+        matches!(
+            self,
+            RespFrame::Null(_) | RespFrame::NullArray(_) | RespFrame::NullBulkString(_)
+        )
+    }
+}
+
+impl<'de> IntoDeserializer<'de, RespError> for RespFrame {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+// deserialize a number, parsing it from a string frame if needed
+macro_rules! deserialize_number {
+    ($($method:ident => $visit:ident),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RespError> {
+                match self.as_text() {
+                    Some(text) => {
+                        let text = text?;
+                        let n = text.parse().map_err(|_| {
+                            RespError::Message(format!("invalid number: {text}"))
+                        })?;
+                        visitor.$visit(n)
+                    }
+                    None => self.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for RespFrame {
+    type Error = RespError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RespError> {
+        if let Some(message) = self.error_message() {
+            return Err(RespError::Message(message));
+        }
+        if let Some(text) = self.as_text() {
+            return match text {
+                Ok(text) => visitor.visit_str(text),
+                // 不是 utf8 的字符串当作 bytes
+                Err(_) => match self {
+                    RespFrame::BulkString(s) => visitor.visit_bytes(&s),
+                    _ => unreachable!("only bulk strings may hold binary data"),
+                },
+            };
+        }
+        match self {
+            RespFrame::Null(_) | RespFrame::NullArray(_) | RespFrame::NullBulkString(_) => {
+                visitor.visit_unit()
+            }
+            RespFrame::Bool(b) => visitor.visit_bool(b),
+            RespFrame::Integer(n) => visitor.visit_i64(n),
+            RespFrame::Double(n) => visitor.visit_f64(n.value()),
+            RespFrame::BigNumber(n) => match i128::try_from(&n) {
+                Ok(n) => visitor.visit_i128(n),
+                Err(_) => visitor.visit_str(&n),
+            },
+            RespFrame::Array(frames) => visit_seq(frames, visitor),
+            RespFrame::Push(frames) => visit_seq(frames, visitor),
+            RespFrame::Set(frames) => visit_seq(frames, visitor),
+            RespFrame::Map(map) => {
+                let mut de = MapDeserializer::new(map.into_iter());
+                let value = visitor.visit_map(&mut de)?;
+                de.end()?;
+                Ok(value)
+            }
+            // 忽略附加的属性
+            RespFrame::Attribute(attr) => attr.into_parts().1.deserialize_any(visitor),
+            _ => unreachable!("string and error frames are handled above"),
+        }
+    }
+
+    deserialize_number! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RespError> {
+        // RESP2 里 bool 是 0/1
+        match self {
+            RespFrame::Integer(0) => visitor.visit_bool(false),
+            RespFrame::Integer(1) => visitor.visit_bool(true),
+            _ => match self.as_text() {
+                Some(text) => match text? {
+                    "0" | "false" => visitor.visit_bool(false),
+                    "1" | "true" => visitor.visit_bool(true),
+                    text => Err(RespError::Message(format!("invalid bool: {text}"))),
+                },
+                None => self.deserialize_any(visitor),
+            },
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RespError> {
+        if self.is_null() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, RespError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RespError> {
+        match self {
+            RespFrame::Array(frames) if frames.len() % 2 == 0 => {
+                let mut frames = frames.into_iter();
+                let pairs = std::iter::from_fn(|| Some((frames.next()?, frames.next()?)));
+                let mut de = MapDeserializer::new(pairs);
+                let value = visitor.visit_map(&mut de)?;
+                de.end()?;
+                Ok(value)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, RespError> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, RespError> {
+        match self {
+            RespFrame::Map(map) if map.len() == 1 => {
+                let (variant, value) = map.into_iter().next().expect("map has one entry");
+                visitor.visit_enum(EnumDeserializer {
+                    variant,
+                    value: Some(value),
+                })
+            }
+            frame if frame.as_text().is_some() => visitor.visit_enum(EnumDeserializer {
+                variant: frame,
+                value: None,
+            }),
+            frame => Err(RespError::Message(format!(
+                "expected a string or a map with one entry for an enum, got {frame:?}"
+            ))),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct identifier ignored_any
+    }
+}
+
+fn visit_seq<'de, V: Visitor<'de>>(
+    frames: impl IntoIterator<Item = RespFrame>,
+    visitor: V,
+) -> Result<V::Value, RespError> {
+    let mut de = SeqDeserializer::new(frames.into_iter());
+    let value = visitor.visit_seq(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+// enum: "Variant" or {"Variant": value}
+struct EnumDeserializer {
+    variant: RespFrame,
+    value: Option<RespFrame>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer {
+    type Error = RespError;
+    type Variant = VariantDeserializer;
+
+    fn variant_seed<T: de::DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<(T::Value, VariantDeserializer), RespError> {
+        let variant = seed.deserialize(self.variant)?;
+        Ok((variant, VariantDeserializer { value: self.value }))
+    }
+}
+
+struct VariantDeserializer {
+    value: Option<RespFrame>,
+}
+
+impl VariantDeserializer {
+    fn value(self) -> Result<RespFrame, RespError> {
+        self.value
+            .ok_or_else(|| RespError::Message("expected a variant with a value".to_string()))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for VariantDeserializer {
+    type Error = RespError;
+
+    fn unit_variant(self) -> Result<(), RespError> {
+        match self.value {
+            None => Ok(()),
+            Some(value) => de::Deserialize::deserialize(value),
+        }
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, RespError> {
+        seed.deserialize(self.value()?)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, RespError> {
+        de::Deserializer::deserialize_seq(self.value()?, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, RespError> {
+        de::Deserializer::deserialize_map(self.value()?, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{to_frame, BulkString, RespArray, RespDecode, RespNull};
+    use bytes::BytesMut;
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct User {
+        name: String,
+        age: u8,
+        score: f64,
+        email: Option<String>,
+        tags: Vec<String>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Event {
+        Login,
+        Visit(String),
+        Move { x: i32, y: i32 },
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<(), RespError> {
+        let user = User {
+            name: "alice".to_string(),
+            age: 30,
+            score: 1.5,
+            email: Some("a@b.c".to_string()),
+            tags: vec!["a".to_string()],
+        };
+        assert_eq!(from_frame::<User>(to_frame(&user)?)?, user);
+
+        for event in [
+            Event::Login,
+            Event::Visit("/".to_string()),
+            Event::Move { x: 1, y: -1 },
+        ] {
+            assert_eq!(from_frame::<Event>(to_frame(&event)?)?, event);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_hgetall_reply() -> Result<(), RespError> {
+        // 所有字段都是 bulk string
+        let buf = b"*8\r\n$4\r\nname\r\n$3\r\nbob\r\n$3\r\nage\r\n$2\r\n42\r\n$5\r\nscore\r\n$3\r\n0.5\r\n$4\r\ntags\r\n*0\r\n";
+        let frame = RespFrame::decode(&mut BytesMut::from(&buf[..]))?;
+        let user: User = from_frame(frame)?;
+        assert_eq!(
+            user,
+            User {
+                name: "bob".to_string(),
+                age: 42,
+                score: 0.5,
+                email: None,
+                tags: vec![],
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_frame_values() -> Result<(), RespError> {
+        assert_eq!(from_frame::<Option<i64>>(RespNull.into())?, None);
+        assert!(from_frame::<bool>(RespFrame::Integer(1))?);
+        assert_eq!(
+            from_frame::<Vec<u8>>(RespArray::new([1.into(), 2.into()]).into())?,
+            vec![1, 2]
+        );
+        let map: HashMap<String, i64> = from_frame(RespFrame::decode(&mut BytesMut::from(
+            "%1\r\n+a\r\n:+1\r\n",
+        ))?)?;
+        assert_eq!(map.get("a"), Some(&1));
+
+        assert!(from_frame::<u8>(BulkString::from("abc").into()).is_err());
+        assert_eq!(
+            from_frame::<String>(RespFrame::decode(&mut BytesMut::from("-ERR boom\r\n"))?),
+            Err(RespError::Message("ERR boom".to_string()))
+        );
+        Ok(())
+    }
+}
@@ -1,45 +1,163 @@
-use super::{find_crlf, CRLF};
-use crate::{RespDecode, RespEncode, RespResult};
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display, Formatter},
+    hash::{Hash, Hasher},
+    ops::Deref,
+    str::{from_utf8, FromStr},
+};
+
+use bytes::{Buf, BufMut, BytesMut};
+
+use super::{line_length, CRLF};
+use crate::{RespDecode, RespEncode, RespError, RespFrame, RespResult};
 
 // double: ",[<+|->]<integral>[.<fractional>][<E|e>[sign]<exponent>]\r\n"
+// ",inf\r\n", ",-inf\r\n", ",nan\r\n"
 pub(crate) const PREFIX: u8 = b',';
 
-impl RespEncode for f64 {
-    fn encode(&self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(self.byte_size());
-        buf.push(PREFIX);
-        buf.extend_from_slice(to_string(*self).as_bytes());
-        buf.extend_from_slice(CRLF);
-        buf
+// f64 的包装，可以作为 map/set 的 key：
+// 按 f64::total_cmp 排序，所有 NaN 都规范化为同一个值，-0.0 和 0.0 不相等
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RespDouble(f64);
+
+impl RespDouble {
+    pub fn new(f: f64) -> Self {
+        if f.is_nan() {
+            RespDouble(f64::NAN)
+        } else {
+            RespDouble(f)
+        }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+}
+
+impl RespEncode for RespDouble {
+    fn encode_to(&self, buf: &mut impl BufMut) {
+        buf.put_u8(PREFIX);
+        buf.put_slice(self.to_string().as_bytes());
+        buf.put_slice(CRLF);
     }
 
     fn byte_size(&self) -> usize {
-        to_string(*self).len() + 3
+        self.to_string().len() + 3
+    }
+}
+
+impl RespDecode for RespDouble {
+    fn decode(buf: &mut BytesMut) -> RespResult<Self> {
+        let len = Self::expect_length(buf)?;
+        let value = from_utf8(&buf[1..len - CRLF.len()])?.parse()?;
+        buf.advance(len);
+        Ok(value)
+    }
+
+    fn expect_length(buf: &[u8]) -> RespResult<usize> {
+        line_length(buf)
+    }
+}
+
+// 最短的能精确还原的表示，很大或者很小的数用科学计数法
+impl Display for RespDouble {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let v = self.0;
+        if v.is_nan() {
+            f.write_str("nan")
+        } else if v.is_infinite() {
+            f.write_str(if v > 0.0 { "inf" } else { "-inf" })
+        } else if v == 0.0 {
+            // 和 redis 一样是 "0"，保留 -0 的符号
+            f.write_str(if v.is_sign_negative() { "-0" } else { "0" })
+        } else if v.abs() > 1e+8 || v.abs() < 1e-8 {
+            write!(f, "{:+e}", v)
+        } else {
+            write!(f, "{:+}", v)
+        }
+    }
+}
+
+impl FromStr for RespDouble {
+    type Err = RespError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // rust 还接受 "infinity" 等写法，这里只接受 RESP3 规定的格式
+        let f = match s {
+            "inf" | "+inf" => f64::INFINITY,
+            "-inf" => f64::NEG_INFINITY,
+            "nan" | "-nan" => f64::NAN,
+            _ if s
+                .bytes()
+                .any(|b| b.is_ascii_alphabetic() && b != b'e' && b != b'E') =>
+            {
+                return Err(RespError::InvalidFrame(format!("Invalid double: {s}")));
+            }
+            _ => s.parse()?,
+        };
+        Ok(RespDouble::new(f))
+    }
+}
+
+impl PartialEq for RespDouble {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
     }
 }
 
-impl RespDecode for f64 {
-    fn decode(buf: &[u8]) -> RespResult<Self> {
-        let end = find_crlf(buf).ok_or(super::RespError::NotComplete)?;
-        let s = std::str::from_utf8(&buf[1..end])?;
-        Ok(s.parse()?)
+impl Eq for RespDouble {}
+
+impl PartialOrd for RespDouble {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
-fn to_string(f: f64) -> String {
-    if f.abs() > 1e+8 || f.abs() < 1e-8 {
-        format!("{:+e}", f)
-    } else {
-        let sign = if f < 0.0 { "" } else { "+" };
-        format!("{sign}{f}")
+impl Ord for RespDouble {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl Hash for RespDouble {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // 和 total_cmp 一致：bit 相同才相等
+        self.0.to_bits().hash(state);
+    }
+}
+
+impl From<f64> for RespDouble {
+    fn from(f: f64) -> Self {
+        RespDouble::new(f)
+    }
+}
+
+impl From<RespDouble> for f64 {
+    fn from(d: RespDouble) -> Self {
+        d.0
+    }
+}
+
+impl From<f64> for RespFrame {
+    fn from(f: f64) -> Self {
+        RespDouble::new(f).into()
+    }
+}
+
+impl Deref for RespDouble {
+    type Target = f64;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::RespFrame;
+    use crate::BulkString;
     use anyhow::Result;
+    use std::collections::HashSet;
 
     #[test]
     fn test_double_encode() {
@@ -56,14 +174,77 @@ mod tests {
         assert_eq!(&frame.encode(), b",-1.23456e-9\r\n");
     }
 
+    #[test]
+    fn test_double_encode_special() {
+        let frame: RespFrame = f64::INFINITY.into();
+        assert_eq!(frame.encode(), b",inf\r\n");
+        assert_eq!(frame.byte_size(), 6);
+
+        let frame: RespFrame = f64::NEG_INFINITY.into();
+        assert_eq!(frame.encode(), b",-inf\r\n");
+
+        let frame: RespFrame = f64::NAN.into();
+        assert_eq!(frame.encode(), b",nan\r\n");
+
+        let frame: RespFrame = 0.0.into();
+        assert_eq!(frame.encode(), b",0\r\n");
+        assert_eq!(frame.byte_size(), 4);
+        assert_eq!(frame.into_resp2(), BulkString::from("0").into());
+        let frame: RespFrame = (-0.0).into();
+        assert_eq!(frame.encode(), b",-0\r\n");
+    }
+
     #[test]
     fn test_double_decode() -> Result<()> {
-        let f = f64::decode(b",123.45\r\n")?;
-        assert_eq!(f, 123.45);
+        let f = RespDouble::decode(&mut BytesMut::from(",123.45\r\n"))?;
+        assert_eq!(*f, 123.45);
 
-        let f = f64::decode(b",+1.23456e-9\r\n")?;
-        assert_eq!(f, 1.23456e-9);
+        let f = RespDouble::decode(&mut BytesMut::from(",+1.23456e-9\r\n"))?;
+        assert_eq!(*f, 1.23456e-9);
 
+        let f = RespDouble::decode(&mut BytesMut::from(",inf\r\n"))?;
+        assert_eq!(*f, f64::INFINITY);
+        let f = RespDouble::decode(&mut BytesMut::from(",-inf\r\n"))?;
+        assert_eq!(*f, f64::NEG_INFINITY);
+        let f = RespDouble::decode(&mut BytesMut::from(",nan\r\n"))?;
+        assert!(f.is_nan());
+
+        assert!(RespDouble::decode(&mut BytesMut::from(",infinity\r\n")).is_err());
         Ok(())
     }
+
+    #[test]
+    fn test_double_round_trip() -> Result<()> {
+        for f in [
+            0.1,
+            1.0 / 3.0,
+            -2.5e-300,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+            123456789.123,
+            99999999.99999999,
+            0.0,
+            -0.0,
+        ] {
+            let buf = RespDouble::new(f).encode();
+            let decoded = RespDouble::decode(&mut BytesMut::from(&buf[..]))?;
+            assert_eq!(decoded.value().to_bits(), f.to_bits());
+        }
+        assert_eq!(RespDouble::new(0.1).to_string(), "+0.1");
+        Ok(())
+    }
+
+    #[test]
+    fn test_double_ord() {
+        assert_eq!(RespDouble::new(f64::NAN), RespDouble::new(-f64::NAN));
+        assert_ne!(RespDouble::new(0.0), RespDouble::new(-0.0));
+        assert!(RespDouble::new(f64::NEG_INFINITY) < RespDouble::new(-1.0));
+        assert!(RespDouble::new(1.0) < RespDouble::new(f64::INFINITY));
+
+        let set: HashSet<_> = [f64::NAN, -f64::NAN, 1.0, 1.0]
+            .into_iter()
+            .map(RespDouble::new)
+            .collect();
+        assert_eq!(set.len(), 2);
+    }
 }
@@ -1,67 +1,138 @@
 use super::{
-    array, bool, bulk_error, bulk_string, double, integer, map, null, set, simple_error,
-    simple_string,
+    array, attribute, big_number, bool, bulk_error, bulk_string, double, frame_length, integer,
+    map, null, push, set, simple_error, simple_string, stream, verbatim_string,
 };
 use crate::{
-    BulkError, BulkString, RespArray, RespDecode, RespError, RespMap, RespNull, RespResult,
-    RespSet, SimpleError, SimpleString,
+    BulkError, BulkString, RespArray, RespAttribute, RespBigNumber, RespDecode, RespDouble,
+    RespError, RespLimits, RespMap, RespNull, RespNullArray, RespNullBulkString, RespPush,
+    RespResult, RespSet, RespVerbatimString, SimpleError, SimpleString,
 };
+use bytes::BytesMut;
 use enum_dispatch::enum_dispatch;
 use tracing::debug;
 
+// RespFrame 可以作为 map/set 的 key，所以需要 Eq/Hash/Ord，不同类型按变体声明顺序排序
 #[enum_dispatch(RespEncode)]
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum RespFrame {
     Array(RespArray),
+    Attribute(RespAttribute),
+    BigNumber(RespBigNumber),
     Bool(bool),
     BulkError(BulkError),
     BulkString(BulkString),
-    Double(f64), //f64不能直接实现Eq和Hash，将来可以包装成自定义类型
+    Double(RespDouble),
     Integer(i64),
     Map(RespMap),
     Null(RespNull),
+    NullArray(RespNullArray),
+    NullBulkString(RespNullBulkString),
+    Push(RespPush),
     Set(RespSet),
     SimpleError(SimpleError),
     SimpleString(SimpleString),
+    VerbatimString(RespVerbatimString),
 }
 
 impl RespDecode for RespFrame {
-    fn decode(buf: &[u8]) -> RespResult<Self> {
-        let prefix = buf.first().ok_or(crate::RespError::NotComplete)?;
+    fn decode(buf: &mut BytesMut) -> RespResult<Self> {
+        let prefix = buf.first().ok_or(RespError::NotComplete)?;
         debug!("Decoding frame: {buf:?}");
         match *prefix {
+            array::PREFIX if buf.starts_with(array::NULL) => {
+                RespNullArray::decode(buf).map(RespFrame::NullArray)
+            }
             array::PREFIX => RespArray::decode(buf).map(RespFrame::Array),
+            attribute::PREFIX => RespAttribute::decode(buf).map(RespFrame::Attribute),
+            big_number::PREFIX => RespBigNumber::decode(buf).map(RespFrame::BigNumber),
             bool::PREFIX => bool::decode(buf).map(RespFrame::Bool),
             bulk_error::PREFIX => BulkError::decode(buf).map(RespFrame::BulkError),
+            bulk_string::PREFIX if buf.starts_with(bulk_string::NULL) => {
+                RespNullBulkString::decode(buf).map(RespFrame::NullBulkString)
+            }
             bulk_string::PREFIX => BulkString::decode(buf).map(RespFrame::BulkString),
-            double::PREFIX => f64::decode(buf).map(RespFrame::Double),
+            double::PREFIX => RespDouble::decode(buf).map(RespFrame::Double),
             integer::PREFIX => i64::decode(buf).map(RespFrame::Integer),
             map::PREFIX => RespMap::decode(buf).map(RespFrame::Map),
             null::PREFIX => RespNull::decode(buf).map(RespFrame::Null),
+            push::PREFIX => RespPush::decode(buf).map(RespFrame::Push),
             set::PREFIX => RespSet::decode(buf).map(RespFrame::Set),
             simple_error::PREFIX => SimpleError::decode(buf).map(RespFrame::SimpleError),
             simple_string::PREFIX => SimpleString::decode(buf).map(RespFrame::SimpleString),
+            verbatim_string::PREFIX => {
+                RespVerbatimString::decode(buf).map(RespFrame::VerbatimString)
+            }
             _ => Err(RespError::InvalidFrameType(format!(
                 "Invalid frame type: {prefix}",
             ))),
         }
     }
+
+    fn expect_length(buf: &[u8]) -> RespResult<usize> {
+        frame_length(buf, &RespLimits::default())
+    }
+}
+
+impl RespFrame {
+    // decode a frame that is known to be complete and within limits,
+    // nested aggregates are not scanned again
+    pub(crate) fn decode_complete(buf: &mut BytesMut) -> RespResult<Self> {
+        match buf.first() {
+            Some(&array::PREFIX) if !buf.starts_with(array::NULL) => {
+                RespArray::decode_complete(buf).map(RespFrame::Array)
+            }
+            Some(&attribute::PREFIX) => {
+                RespAttribute::decode_complete(buf).map(RespFrame::Attribute)
+            }
+            Some(&map::PREFIX) => RespMap::decode_complete(buf).map(RespFrame::Map),
+            Some(&push::PREFIX) => RespPush::decode_complete(buf).map(RespFrame::Push),
+            Some(&set::PREFIX) => RespSet::decode_complete(buf).map(RespFrame::Set),
+            Some(&bulk_string::PREFIX) if stream::is_streamed(buf) => {
+                stream::decode_chunks(buf).map(|data| BulkString::new(data).into())
+            }
+            _ => Self::decode(buf),
+        }
+    }
 }
 
 impl From<&str> for RespFrame {
     fn from(s: &str) -> Self {
-        SimpleString(s.to_string()).into()
+        SimpleString::new(s.to_string()).into()
     }
 }
 
 impl From<&[u8]> for RespFrame {
     fn from(s: &[u8]) -> Self {
-        BulkString(s.to_vec()).into()
+        BulkString::from(s).into()
     }
 }
 
 impl<const N: usize> From<&[u8; N]> for RespFrame {
     fn from(s: &[u8; N]) -> Self {
-        BulkString(s.to_vec()).into()
+        BulkString::from(s).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_frame_ord() {
+        assert_eq!(RespFrame::from(f64::NAN), RespFrame::from(f64::NAN));
+        assert_ne!(RespFrame::from(0.0), RespFrame::from(-0.0));
+        assert!(RespFrame::from(-1.5) < RespFrame::from(2.0));
+        assert!(RespFrame::Integer(1) < RespFrame::Integer(2));
+        // 不同类型按变体顺序
+        assert!(RespFrame::Integer(1) < RespFrame::from("1"));
+        assert_ne!(RespFrame::Integer(1), RespFrame::from(1.0));
+
+        let mut set = HashSet::new();
+        set.insert(RespFrame::from(f64::NAN));
+        set.insert(RespFrame::from(f64::NAN));
+        set.insert(RespFrame::from(b"a"));
+        set.insert(RespFrame::from(b"a"));
+        assert_eq!(set.len(), 2);
     }
 }
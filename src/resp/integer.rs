@@ -1,19 +1,19 @@
 use std::str::from_utf8;
 
+use bytes::{Buf, BufMut, BytesMut};
+
 use crate::{RespDecode, RespEncode, RespResult};
 
-use super::{find_crlf, CRLF};
+use super::{line_length, CRLF};
 
 // integer: ":[<+|->]<value>\r\n"
 pub(crate) const PREFIX: u8 = b':';
 
 impl RespEncode for i64 {
-    fn encode(&self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(self.byte_size());
-        buf.push(PREFIX);
-        buf.extend_from_slice(to_string(*self).as_bytes());
-        buf.extend_from_slice(CRLF);
-        buf
+    fn encode_to(&self, buf: &mut impl BufMut) {
+        buf.put_u8(PREFIX);
+        buf.put_slice(to_string(*self).as_bytes());
+        buf.put_slice(CRLF);
     }
 
     fn byte_size(&self) -> usize {
@@ -22,10 +22,16 @@ impl RespEncode for i64 {
 }
 
 impl RespDecode for i64 {
-    fn decode(buf: &[u8]) -> RespResult<Self> {
-        let end = find_crlf(buf).ok_or(super::RespError::NotComplete)?;
-        let s = from_utf8(&buf[1..end])?;
-        Ok(s.parse()?)
+    fn decode(buf: &mut BytesMut) -> RespResult<Self> {
+        let len = Self::expect_length(buf)?;
+        let s = from_utf8(&buf[1..len - CRLF.len()])?;
+        let value = s.parse()?;
+        buf.advance(len);
+        Ok(value)
+    }
+
+    fn expect_length(buf: &[u8]) -> RespResult<usize> {
+        line_length(buf)
     }
 }
 
@@ -51,10 +57,10 @@ mod tests {
 
     #[test]
     fn test_integer_decode() -> Result<()> {
-        let frame = i64::decode(b":+123\r\n")?;
+        let frame = i64::decode(&mut BytesMut::from(":+123\r\n"))?;
         assert_eq!(frame, 123);
 
-        let frame = i64::decode(b":-123\r\n")?;
+        let frame = i64::decode(&mut BytesMut::from(":-123\r\n"))?;
         assert_eq!(frame, -123);
 
         Ok(())
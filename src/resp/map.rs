@@ -1,65 +1,148 @@
-use crate::{RespDecode, RespEncode, RespFrame, RespResult, SimpleString};
+use crate::{RespDecode, RespEncode, RespFrame, RespLimits, RespResult};
+use bytes::{Buf, BufMut, BytesMut};
+use indexmap::IndexMap;
 use std::{
-    collections::BTreeMap,
+    cmp::Ordering,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
     ops::{Deref, DerefMut},
 };
 
-use super::read_len;
+use super::{capacity_hint, frame_length, read_len, stream};
 
 pub(crate) const PREFIX: u8 = b'%';
 
 // map: "%<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n>"
-// only support string keys
-#[derive(Debug, Clone, PartialEq, Default)]
-pub struct RespMap(BTreeMap<String, RespFrame>);
+// key 可以是任意帧，按插入顺序编码。
+// 比较时和顺序无关：相等/哈希按条目集合计算，排序按排好序的条目比较
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RespMap(IndexMap<RespFrame, RespFrame>);
 
 impl RespMap {
     pub fn new() -> Self {
         Self::default()
     }
+
+    // entries sorted by key, used to compare maps regardless of insertion order
+    fn sorted_entries(&self) -> Vec<(&RespFrame, &RespFrame)> {
+        let mut entries: Vec<_> = self.iter().collect();
+        entries.sort();
+        entries
+    }
 }
 
 impl RespEncode for RespMap {
-    fn encode(&self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(self.byte_size());
-        buf.push(PREFIX);
-        buf.extend_from_slice(self.len().to_string().as_bytes());
-        buf.extend_from_slice(b"\r\n");
-        for (key, value) in self.iter() {
-            buf.extend_from_slice(&SimpleString::new(key).encode());
-            buf.extend_from_slice(&value.encode());
-        }
-        buf
+    fn encode_to(&self, buf: &mut impl BufMut) {
+        buf.put_u8(PREFIX);
+        buf.put_slice(self.len().to_string().as_bytes());
+        buf.put_slice(b"\r\n");
+        self.encode_entries(buf);
     }
 
     fn byte_size(&self) -> usize {
         //%<number-of-entries>\r\n
-        let mut size = self.len().to_string().len() + 3;
+        self.len().to_string().len() + 3 + self.entries_byte_size()
+    }
+}
+
+impl RespDecode for RespMap {
+    fn decode(buf: &mut BytesMut) -> RespResult<Self> {
+        Self::expect_length(buf)?;
+        Self::decode_complete(buf)
+    }
+
+    fn expect_length(buf: &[u8]) -> RespResult<usize> {
+        frame_length(buf, &RespLimits::default())
+    }
+}
+
+impl RespMap {
+    pub(crate) fn decode_complete(buf: &mut BytesMut) -> RespResult<Self> {
+        if stream::is_streamed(buf) {
+            let mut frames = stream::decode_elements(buf)?.into_iter();
+            let entries = std::iter::from_fn(|| Some((frames.next()?, frames.next()?)));
+            return Ok(Self(entries.collect()));
+        }
+        let (num, offset) = read_len(PREFIX, buf)?;
+        buf.advance(offset);
+        Self::decode_entries(buf, num)
+    }
+
+    // the entries are shared with the attribute type
+    pub(crate) fn decode_entries(buf: &mut BytesMut, num: usize) -> RespResult<Self> {
+        let mut map = IndexMap::with_capacity(capacity_hint(num, buf));
+        for _ in 0..num {
+            let key = RespFrame::decode_complete(buf)?;
+            let value = RespFrame::decode_complete(buf)?;
+            map.insert(key, value);
+        }
+        Ok(Self(map))
+    }
+
+    pub(crate) fn encode_entries(&self, buf: &mut impl BufMut) {
+        for (key, value) in self.iter() {
+            key.encode_to(buf);
+            value.encode_to(buf);
+        }
+    }
+
+    pub(crate) fn entries_byte_size(&self) -> usize {
+        let mut size = 0;
         for (key, value) in self.iter() {
-            size += key.len() + 3; //+<key>\r\n
+            size += key.byte_size();
             size += value.byte_size();
         }
         size
     }
 }
 
-impl RespDecode for RespMap {
-    fn decode(buf: &[u8]) -> RespResult<Self> {
-        let (num, mut offset) = read_len(PREFIX, buf)?;
-        let mut map = BTreeMap::new();
-        for _ in 0..num {
-            let key = SimpleString::decode(&buf[offset..])?;
-            offset += key.byte_size();
-            let value = RespFrame::decode(&buf[offset..])?;
-            offset += value.byte_size();
-            map.insert(key.0, value);
+impl Hash for RespMap {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // 与顺序无关：每个条目单独哈希后相加
+        let mut sum: u64 = 0;
+        for entry in self.iter() {
+            let mut hasher = DefaultHasher::new();
+            entry.hash(&mut hasher);
+            sum = sum.wrapping_add(hasher.finish());
         }
-        Ok(Self(map))
+        self.len().hash(state);
+        sum.hash(state);
+    }
+}
+
+impl PartialOrd for RespMap {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RespMap {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sorted_entries().cmp(&other.sorted_entries())
+    }
+}
+
+impl<K: Into<RespFrame>, V: Into<RespFrame>> FromIterator<(K, V)> for RespMap {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        )
+    }
+}
+
+impl IntoIterator for RespMap {
+    type Item = (RespFrame, RespFrame);
+    type IntoIter = indexmap::map::IntoIter<RespFrame, RespFrame>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
     }
 }
 
 impl Deref for RespMap {
-    type Target = BTreeMap<String, RespFrame>;
+    type Target = IndexMap<RespFrame, RespFrame>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -81,13 +164,10 @@ mod tests {
     #[test]
     fn test_map_encode() {
         let mut map = RespMap::new();
-        map.insert(
-            "hello".to_string(),
-            BulkString::new("world".to_string()).into(),
-        );
-        map.insert("foo".to_string(), (-123456.789).into());
+        map.insert("hello".into(), BulkString::from("world").into());
+        map.insert("foo".into(), (-123456.789).into());
 
-        let buf = b"%2\r\n+foo\r\n,-123456.789\r\n+hello\r\n$5\r\nworld\r\n";
+        let buf = b"%2\r\n+hello\r\n$5\r\nworld\r\n+foo\r\n,-123456.789\r\n";
         let frame: RespFrame = map.into();
         assert_eq!(&frame.encode(), buf);
         assert_eq!(frame.byte_size(), buf.len());
@@ -96,16 +176,45 @@ mod tests {
     #[test]
     fn test_map_decode() -> Result<()> {
         let buf = b"%2\r\n+hello\r\n$5\r\nworld\r\n+foo\r\n$3\r\nbar\r\n";
-        let frame = RespMap::decode(buf)?;
+        let frame = RespMap::decode(&mut BytesMut::from(&buf[..]))?;
         let mut map = RespMap::new();
-        map.insert(
-            "hello".to_string(),
-            BulkString::new(b"world".to_vec()).into(),
-        );
-        map.insert("foo".to_string(), BulkString::new(b"bar".to_vec()).into());
+        map.insert("hello".into(), BulkString::from("world").into());
+        map.insert("foo".into(), BulkString::from("bar").into());
         assert_eq!(frame, map);
         assert_eq!(frame.byte_size(), buf.len());
+        assert_eq!(frame.encode(), buf);
 
         Ok(())
     }
+
+    #[test]
+    fn test_map_any_keys() -> Result<()> {
+        let buf = b"%3\r\n$5\r\nproto\r\n:+3\r\n:+1\r\n#t\r\n,+1.5\r\n_\r\n";
+        let map = RespMap::decode(&mut BytesMut::from(&buf[..]))?;
+        let keys: Vec<_> = map.keys().cloned().collect();
+        assert_eq!(
+            keys,
+            vec![BulkString::from("proto").into(), 1.into(), 1.5.into()]
+        );
+        assert_eq!(map.get(&RespFrame::from(1)), Some(&true.into()));
+        assert_eq!(map.encode(), buf);
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_compare() {
+        let a: RespMap = [("a", 1), ("b", 2)].into_iter().collect();
+        let b: RespMap = [("b", 2), ("a", 1)].into_iter().collect();
+        let c: RespMap = [("a", 1), ("b", 3)].into_iter().collect();
+        assert_eq!(a, b);
+        assert_eq!(a.cmp(&b), Ordering::Equal);
+        assert!(a < c);
+
+        let hash = |m: &RespMap| {
+            let mut hasher = DefaultHasher::new();
+            m.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&a), hash(&b));
+    }
 }
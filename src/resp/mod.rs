@@ -13,42 +13,73 @@
 ///     - boolean: "#<t|f>\r\n"
 ///     - double: ",[<+|->]<integral>[.<fractional>][<E|e>[sign]<exponent>]\r\n"
 ///     - big number: "([+|-]<number>\r\n"
+///     - verbatim string: "=<length>\r\n<format>:<data>\r\n"
 ///     - map: "%<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n>"
 ///     - set: "~<number-of-elements>\r\n<element-1>...<element-n>"
+///     - push: "><number-of-elements>\r\n<element-1>...<element-n>"
+///     - attribute: "|<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n><reply>"
+///     - streamed string: "$?\r\n;<length>\r\n<data>\r\n...;0\r\n"
+///     - streamed aggregate: "*?\r\n<element-1>...<element-n>.\r\n", also "%?" and "~?"
 ///     - ...
 /// - enum RespFrame {}
 /// - trait RespEncode / RespDecode (enum dispatch)
 /// - bytes trait
 ///
 mod array;
+mod attribute;
+mod big_number;
 mod bool;
 mod bulk_error;
 mod bulk_string;
+#[cfg(feature = "serde")]
+mod de;
 mod double;
 mod frame;
 mod integer;
 mod map;
 mod null;
+mod pretty;
+mod protocol;
+mod push;
+#[cfg(feature = "serde")]
+mod ser;
 mod set;
 mod simple_error;
 mod simple_string;
+mod stream;
+mod verbatim_string;
 
 use std::str::from_utf8;
 
+use bytes::{BufMut, BytesMut};
 use enum_dispatch::enum_dispatch;
 use thiserror::Error;
 
-pub use array::RespArray;
+pub use array::{RespArray, RespNullArray};
+pub use attribute::RespAttribute;
+pub use big_number::RespBigNumber;
 pub use bulk_error::BulkError;
-pub use bulk_string::BulkString;
+pub use bulk_string::{BulkString, RespNullBulkString};
+#[cfg(feature = "serde")]
+pub use de::from_frame;
+pub use double::RespDouble;
 pub use frame::RespFrame;
 pub use map::RespMap;
 pub use null::RespNull;
+pub use pretty::PrettyFrame;
+pub use protocol::RespProtocol;
+pub use push::RespPush;
+#[cfg(feature = "serde")]
+pub use ser::to_frame;
 pub use set::RespSet;
 pub use simple_error::SimpleError;
 pub use simple_string::SimpleString;
+pub use stream::RespStreamPart;
+pub use verbatim_string::RespVerbatimString;
 
 const CRLF: &[u8] = b"\r\n";
+// parse_header 中表示 "$-1" 和 "*-1"
+const NULL_LEN: usize = usize::MAX;
 const NULL: &[u8] = b"_\r\n";
 const TRUE: &[u8] = b"#t\r\n";
 const FALSE: &[u8] = b"#f\r\n";
@@ -65,6 +96,11 @@ pub enum RespError {
     InvalidFrameLength(isize),
     #[error("Frame is not complete")]
     NotComplete,
+    #[error("Protocol error: {0}")]
+    LimitExceeded(String),
+    // serde 转换时的错误
+    #[error("{0}")]
+    Message(String),
 
     #[error("{0}")]
     ParseIntError(#[from] std::num::ParseIntError),
@@ -78,24 +114,488 @@ pub enum RespError {
 
 pub type RespResult<T> = Result<T, RespError>;
 
+// 协议安全限制，类似 Redis 的 proto-max-bulk-len 和 multibulk 长度限制
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RespLimits {
+    // max length of a bulk string or bulk error
+    pub max_bulk_len: usize,
+    // max number of frames in an aggregate, a map entry counts as two frames
+    pub max_aggregate_len: usize,
+    // max nesting depth of aggregates
+    pub max_depth: usize,
+    // max length of a header or simple line (without CRLF), same as redis' 64k
+    pub max_line_len: usize,
+}
+
+impl Default for RespLimits {
+    fn default() -> Self {
+        Self {
+            max_bulk_len: 512 * 1024 * 1024,
+            max_aggregate_len: 1024 * 1024,
+            max_depth: 128,
+            max_line_len: 64 * 1024,
+        }
+    }
+}
+
+impl RespLimits {
+    // check the length of a line, called while still searching for its CRLF
+    pub(crate) fn check_line(&self, len: usize) -> RespResult<()> {
+        if len > self.max_line_len {
+            return Err(RespError::LimitExceeded("too big header line".to_string()));
+        }
+        Ok(())
+    }
+
+    // check a header found at the given depth of nesting
+    pub(crate) fn check(&self, header: &FrameHeader, depth: usize) -> RespResult<()> {
+        match *header {
+            FrameHeader::Blob(len) if len > self.max_bulk_len => {
+                Err(RespError::LimitExceeded("invalid bulk length".to_string()))
+            }
+            FrameHeader::Aggregate(len) if len > self.max_aggregate_len => Err(
+                RespError::LimitExceeded("invalid multibulk length".to_string()),
+            ),
+            FrameHeader::Aggregate(_) if depth >= self.max_depth => Err(RespError::LimitExceeded(
+                "too many nested aggregates".to_string(),
+            )),
+            FrameHeader::Streamed(prefix)
+                if prefix != bulk_string::PREFIX && depth >= self.max_depth =>
+            {
+                Err(RespError::LimitExceeded(
+                    "too many nested aggregates".to_string(),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
 #[enum_dispatch]
 pub trait RespEncode {
-    fn encode(&self) -> Vec<u8>;
+    // write the encoded frame into buf, no intermediate allocation
+    fn encode_to(&self, buf: &mut impl BufMut);
     fn byte_size(&self) -> usize;
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.byte_size());
+        self.encode_to(&mut buf);
+        buf
+    }
 }
 
 pub trait RespDecode: Sized {
-    fn decode(buf: &[u8]) -> RespResult<Self>;
+    // decode a frame from the front of buf, consumed bytes are split off so that
+    // the decoded frame can share the buffer's allocation.
+    // buf is left untouched if the frame is not complete.
+    fn decode(buf: &mut BytesMut) -> RespResult<Self>;
+    // return the total length of the frame at the front of buf
+    fn expect_length(buf: &[u8]) -> RespResult<usize>;
+}
+
+// the header line of a frame, tells how the rest of the frame is laid out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum FrameHeader {
+    // the header line is the whole frame, e.g. "+OK\r\n", "$-1\r\n"
+    Line,
+    // a blob of the given length and a CRLF follow the header, e.g. "$5\r\nhello\r\n"
+    Blob(usize),
+    // the given number of frames follow the header, e.g. "*2\r\n..."
+    Aggregate(usize),
+    // frames or chunks follow until the end of stream, e.g. "*?\r\n...", "$?\r\n..."
+    Streamed(u8),
+    // end of a streamed frame, ".\r\n" or ";0\r\n"
+    End,
+}
+
+// parse a header line (without CRLF)
+pub(crate) fn parse_header(line: &[u8]) -> RespResult<FrameHeader> {
+    // 空行是错误的帧，不能当成 NotComplete 一直等待
+    let prefix = line
+        .first()
+        .ok_or_else(|| RespError::InvalidFrame("empty frame header".to_string()))?;
+    let signed_len = || -> RespResult<isize> { Ok(from_utf8(&line[1..])?.parse()?) };
+    if &line[1..] == b"?" {
+        return match *prefix {
+            bulk_string::PREFIX | array::PREFIX | map::PREFIX | set::PREFIX => {
+                Ok(FrameHeader::Streamed(*prefix))
+            }
+            _ => Err(RespError::InvalidFrame(format!(
+                "Invalid streamed frame type: {prefix}"
+            ))),
+        };
+    }
+    // 只有 "$-1" 和 "*-1" 表示 null，其它类型的负数长度都是错误
+    let len = || -> RespResult<usize> {
+        match signed_len()? {
+            -1 if *prefix == bulk_string::PREFIX || *prefix == array::PREFIX => Ok(NULL_LEN),
+            n => usize::try_from(n).map_err(|_| RespError::InvalidFrameLength(n)),
+        }
+    };
+    match *prefix {
+        bulk_string::PREFIX | bulk_error::PREFIX | verbatim_string::PREFIX => match len()? {
+            NULL_LEN => Ok(FrameHeader::Line),
+            n => Ok(FrameHeader::Blob(n)),
+        },
+        array::PREFIX | set::PREFIX | push::PREFIX => match len()? {
+            NULL_LEN => Ok(FrameHeader::Line),
+            n => Ok(FrameHeader::Aggregate(n)),
+        },
+        map::PREFIX => Ok(FrameHeader::Aggregate(len()?.saturating_mul(2))),
+        // the entries and the reply that follows
+        attribute::PREFIX => Ok(FrameHeader::Aggregate(
+            len()?.saturating_mul(2).saturating_add(1),
+        )),
+        big_number::PREFIX
+        | bool::PREFIX
+        | double::PREFIX
+        | integer::PREFIX
+        | null::PREFIX
+        | simple_error::PREFIX
+        | simple_string::PREFIX => Ok(FrameHeader::Line),
+        stream::CHUNK_PREFIX => match len()? {
+            0 => Ok(FrameHeader::End),
+            n => Ok(FrameHeader::Blob(n)),
+        },
+        stream::END_PREFIX if line.len() == 1 => Ok(FrameHeader::End),
+        _ => Err(RespError::InvalidFrameType(format!(
+            "Invalid frame type: {prefix}",
+        ))),
+    }
+}
+
+// whether b starts a RESP frame, anything else starts an inline command
+pub(crate) fn is_frame_prefix(b: u8) -> bool {
+    matches!(
+        b,
+        array::PREFIX
+            | attribute::PREFIX
+            | big_number::PREFIX
+            | bool::PREFIX
+            | bulk_error::PREFIX
+            | bulk_string::PREFIX
+            | double::PREFIX
+            | integer::PREFIX
+            | map::PREFIX
+            | null::PREFIX
+            | push::PREFIX
+            | set::PREFIX
+            | simple_error::PREFIX
+            | simple_string::PREFIX
+            | verbatim_string::PREFIX
+    )
 }
 
 // return the position of CRLF
-fn find_crlf(buf: &[u8]) -> Option<usize> {
+pub(crate) fn find_crlf(buf: &[u8]) -> Option<usize> {
     buf.windows(2).position(|w| w == CRLF)
 }
 
-// read the length of the frame, return (length, end_position)
-fn read_len(_prefix: u8, buf: &[u8]) -> RespResult<(isize, usize)> {
+// read the length of the frame, return (length, end_position).
+// a negative length is an error, see read_nullable_len for "$-1" and "*-1"
+fn read_len(_prefix: u8, buf: &[u8]) -> RespResult<(usize, usize)> {
+    match read_nullable_len(buf)? {
+        (Some(len), end) => Ok((len, end)),
+        (None, _) => Err(RespError::InvalidFrameLength(-1)),
+    }
+}
+
+// read the length of a bulk string or an array, None for null
+fn read_nullable_len(buf: &[u8]) -> RespResult<(Option<usize>, usize)> {
     let end = find_crlf(buf).ok_or(RespError::NotComplete)?;
     let s = from_utf8(&buf[1..end])?;
-    Ok((s.parse().map_err(RespError::ParseIntError)?, end + 2))
+    let len: isize = s.parse().map_err(RespError::ParseIntError)?;
+    match len {
+        -1 => Ok((None, end + 2)),
+        n => {
+            let n = usize::try_from(n).map_err(|_| RespError::InvalidFrameLength(n))?;
+            Ok((Some(n), end + 2))
+        }
+    }
+}
+
+// 聚合类型预先分配的大小：每个元素至少 3 个字节（例如 "_\r\n"），
+// 不能直接使用客户端发送的长度
+fn capacity_hint(count: usize, buf: &[u8]) -> usize {
+    count.min(buf.len() / 3)
+}
+
+// length of a single line frame, e.g. "+OK\r\n"
+fn line_length(buf: &[u8]) -> RespResult<usize> {
+    let end = find_crlf(buf).ok_or(RespError::NotComplete)?;
+    Ok(end + CRLF.len())
+}
+
+// total length of the frame at the front of buf, checked against limits.
+// 不递归，嵌套很深的帧也不会耗尽栈
+fn frame_length(buf: &[u8], limits: &RespLimits) -> RespResult<usize> {
+    let mut scanner = FrameScanner::new(*limits);
+    let mut pos = 0;
+    loop {
+        let Some(end) = find_crlf(&buf[pos..]).map(|i| pos + i) else {
+            // the last byte may be the '\r' of a CRLF
+            limits.check_line((buf.len() - pos).saturating_sub(1))?;
+            return Err(RespError::NotComplete);
+        };
+        limits.check_line(end - pos)?;
+        let header = scanner.header(&buf[pos..end])?;
+        pos = end + CRLF.len();
+        if let FrameHeader::Blob(len) = header {
+            pos += len + CRLF.len();
+            if buf.len() < pos {
+                return Err(RespError::NotComplete);
+            }
+        }
+        if scanner.complete(header)? {
+            return Ok(pos);
+        }
+    }
+}
+
+// an aggregate whose elements are being scanned
+#[derive(Debug, PartialEq)]
+pub(crate) enum OpenAggregate {
+    // remaining number of frames
+    Sized(usize),
+    // a streamed aggregate or string, ends with "." or ";0".
+    // count is the number of frames, or the number of bytes for a string
+    Streamed { prefix: u8, count: usize },
+}
+
+// 逐个检查帧头，记录还没结束的聚合类型，frame_length 和 RespFrameCodec 共用
+#[derive(Debug, Default)]
+pub(crate) struct FrameScanner {
+    limits: RespLimits,
+    // innermost last
+    pub(crate) open: Vec<OpenAggregate>,
+}
+
+impl FrameScanner {
+    pub(crate) fn new(limits: RespLimits) -> Self {
+        Self {
+            limits,
+            open: Vec::new(),
+        }
+    }
+
+    pub(crate) fn limits(&self) -> &RespLimits {
+        &self.limits
+    }
+
+    // parse a header line (without CRLF) and check it against the limits
+    // and the enclosing aggregate. may be called again for the same line.
+    pub(crate) fn header(&self, line: &[u8]) -> RespResult<FrameHeader> {
+        let header = parse_header(line)?;
+        self.limits.check(&header, self.open.len())?;
+        let in_string = match self.open.last() {
+            Some(OpenAggregate::Streamed { prefix, count }) if *prefix == bulk_string::PREFIX => {
+                Some(*count)
+            }
+            _ => None,
+        };
+        match (line[0], in_string) {
+            (stream::CHUNK_PREFIX, Some(count)) => {
+                if let FrameHeader::Blob(len) = header {
+                    if count + len > self.limits.max_bulk_len {
+                        return Err(RespError::LimitExceeded("invalid bulk length".to_string()));
+                    }
+                }
+            }
+            (_, Some(_)) => {
+                return Err(RespError::InvalidFrame(
+                    "expected a chunk of streamed string".to_string(),
+                ))
+            }
+            (stream::CHUNK_PREFIX, None) => {
+                return Err(RespError::InvalidFrame(
+                    "chunk outside of streamed string".to_string(),
+                ))
+            }
+            (stream::END_PREFIX, None) => match self.open.last() {
+                Some(OpenAggregate::Streamed { prefix, count })
+                    if *prefix != map::PREFIX || count % 2 == 0 => {}
+                Some(OpenAggregate::Streamed { .. }) => {
+                    return Err(RespError::InvalidFrame(
+                        "streamed map ends without a value".to_string(),
+                    ))
+                }
+                _ => {
+                    return Err(RespError::InvalidFrame(
+                        "end of stream outside of streamed aggregate".to_string(),
+                    ))
+                }
+            },
+            _ => {}
+        }
+        Ok(header)
+    }
+
+    // the frame of the header and the data after it are available,
+    // return true if the outermost frame is complete
+    pub(crate) fn complete(&mut self, header: FrameHeader) -> RespResult<bool> {
+        match header {
+            FrameHeader::Aggregate(count) if count > 0 => {
+                self.open.push(OpenAggregate::Sized(count));
+                return Ok(false);
+            }
+            FrameHeader::Streamed(prefix) => {
+                self.open.push(OpenAggregate::Streamed { prefix, count: 0 });
+                return Ok(false);
+            }
+            FrameHeader::End => {
+                // the streamed frame is complete
+                self.open.pop();
+            }
+            _ => {}
+        }
+        loop {
+            match self.open.last_mut() {
+                None => return Ok(true),
+                Some(OpenAggregate::Sized(remaining)) => {
+                    *remaining -= 1;
+                    if *remaining > 0 {
+                        return Ok(false);
+                    }
+                    self.open.pop();
+                }
+                Some(OpenAggregate::Streamed { prefix, count }) => {
+                    if *prefix == bulk_string::PREFIX {
+                        if let FrameHeader::Blob(len) = header {
+                            *count += len;
+                        }
+                    } else {
+                        *count += 1;
+                        if *count > self.limits.max_aggregate_len {
+                            return Err(RespError::LimitExceeded(
+                                "invalid multibulk length".to_string(),
+                            ));
+                        }
+                    }
+                    return Ok(false);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits() {
+        let limits = RespLimits {
+            max_bulk_len: 5,
+            max_aggregate_len: 2,
+            max_depth: 2,
+            max_line_len: 8,
+        };
+        assert_eq!(frame_length(b"$5\r\nhello\r\n", &limits), Ok(11));
+        assert_eq!(
+            frame_length(b"$6\r\nhello!\r\n", &limits),
+            Err(RespError::LimitExceeded("invalid bulk length".to_string()))
+        );
+        assert_eq!(
+            frame_length(b"*3\r\n", &limits),
+            Err(RespError::LimitExceeded(
+                "invalid multibulk length".to_string()
+            ))
+        );
+        assert_eq!(
+            frame_length(b"%2\r\n", &limits),
+            Err(RespError::LimitExceeded(
+                "invalid multibulk length".to_string()
+            ))
+        );
+        assert_eq!(frame_length(b"*1\r\n*1\r\n:+1\r\n", &limits), Ok(13));
+        // streamed frames are limited by their total size
+        assert_eq!(
+            frame_length(b"$?\r\n;3\r\nabc\r\n;3\r\ndef\r\n;0\r\n", &limits),
+            Err(RespError::LimitExceeded("invalid bulk length".to_string()))
+        );
+        assert_eq!(
+            frame_length(b"*?\r\n:+1\r\n:+2\r\n:+3\r\n.\r\n", &limits),
+            Err(RespError::LimitExceeded(
+                "invalid multibulk length".to_string()
+            ))
+        );
+        assert_eq!(
+            frame_length(b"*1\r\n*1\r\n*1\r\n:+1\r\n", &limits),
+            Err(RespError::LimitExceeded(
+                "too many nested aggregates".to_string()
+            ))
+        );
+        // header lines are limited even before the CRLF arrives
+        assert_eq!(frame_length(b"+abcdefg\r\n", &limits), Ok(10));
+        assert_eq!(
+            frame_length(b"+abcdefg\r", &limits),
+            Err(RespError::NotComplete)
+        );
+        let too_big = Err(RespError::LimitExceeded("too big header line".to_string()));
+        assert_eq!(frame_length(b"+abcdefgh\r\n", &limits), too_big);
+        assert_eq!(frame_length(b"*000000001", &limits), too_big);
+        assert_eq!(frame_length(b"*1\r\n:123456789", &limits), too_big);
+    }
+
+    #[test]
+    fn test_negative_length() {
+        // 只有 "$-1" 和 "*-1" 是 null
+        for input in [
+            &b"%-1\r\n"[..],
+            b"~-1\r\n",
+            b">-1\r\n",
+            b"!-1\r\n",
+            b"=-1\r\n",
+            b"|-1\r\n",
+            b"*1\r\n%-1\r\n",
+            b"$-2\r\n",
+            b"*-2\r\n",
+        ] {
+            assert!(
+                matches!(
+                    RespFrame::decode(&mut BytesMut::from(input)),
+                    Err(RespError::InvalidFrameLength(_))
+                ),
+                "{input:?}"
+            );
+        }
+        assert_eq!(
+            RespFrame::decode(&mut BytesMut::from("$-1\r\n")),
+            Ok(RespNullBulkString.into())
+        );
+        assert_eq!(
+            RespFrame::decode(&mut BytesMut::from("*-1\r\n")),
+            Ok(RespNullArray.into())
+        );
+    }
+
+    #[test]
+    fn test_empty_header() {
+        // 空行不能被当成还没收完的帧
+        let err = || RespError::InvalidFrame("empty frame header".to_string());
+        assert_eq!(
+            RespFrame::decode(&mut BytesMut::from("*1\r\n\r\n")),
+            Err(err())
+        );
+        assert_eq!(RespFrame::expect_length(b"*1\r\n\r\n"), Err(err()));
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        let buf = b"*1\r\n".repeat(100_000);
+        assert!(matches!(
+            RespFrame::decode(&mut BytesMut::from(&buf[..])),
+            Err(RespError::LimitExceeded(_))
+        ));
+        assert_eq!(
+            RespFrame::expect_length(b"*9999999999\r\n"),
+            Err(RespError::LimitExceeded(
+                "invalid multibulk length".to_string()
+            ))
+        );
+        assert_eq!(
+            RespFrame::expect_length(b"$-2\r\n"),
+            Err(RespError::InvalidFrameLength(-2))
+        );
+    }
 }
@@ -1,3 +1,5 @@
+use bytes::{Buf, BufMut, BytesMut};
+
 use crate::{RespDecode, RespEncode, RespError, RespResult};
 
 use super::NULL;
@@ -6,12 +8,12 @@ use super::NULL;
 pub(crate) const PREFIX: u8 = b'_';
 const BYTE_SIZE: usize = 3;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct RespNull;
 
 impl RespEncode for RespNull {
-    fn encode(&self) -> Vec<u8> {
-        NULL.to_vec()
+    fn encode_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(NULL);
     }
 
     fn byte_size(&self) -> usize {
@@ -20,11 +22,10 @@ impl RespEncode for RespNull {
 }
 
 impl RespDecode for RespNull {
-    fn decode(buf: &[u8]) -> RespResult<Self> {
-        if buf.len() < BYTE_SIZE {
-            return Err(RespError::NotComplete);
-        }
+    fn decode(buf: &mut BytesMut) -> RespResult<Self> {
+        let len = Self::expect_length(buf)?;
         if buf.starts_with(NULL) {
+            buf.advance(len);
             Ok(RespNull)
         } else {
             Err(RespError::InvalidFrame(format!(
@@ -33,6 +34,13 @@ impl RespDecode for RespNull {
             )))
         }
     }
+
+    fn expect_length(buf: &[u8]) -> RespResult<usize> {
+        if buf.len() < BYTE_SIZE {
+            return Err(RespError::NotComplete);
+        }
+        Ok(BYTE_SIZE)
+    }
 }
 
 #[cfg(test)]
@@ -55,11 +63,19 @@ mod tests {
 
     #[test]
     fn test_decode() {
-        assert!(RespNull::decode(NULL).is_ok());
-        assert!(RespNull::decode(b"_\r\n").is_ok());
-        assert!(RespNull::decode(b"_\r\nextra").is_ok());
-        assert_eq!(RespNull::decode(b"_"), Err(RespError::NotComplete));
-        assert!(RespNull::decode(b"_\r\r").is_err());
-        assert_eq!(RespFrame::decode(NULL), Ok(RespNull.into()));
+        assert!(RespNull::decode(&mut BytesMut::from(NULL)).is_ok());
+        assert!(RespNull::decode(&mut BytesMut::from("_\r\n")).is_ok());
+        let mut buf = BytesMut::from("_\r\nextra");
+        assert!(RespNull::decode(&mut buf).is_ok());
+        assert_eq!(buf, "extra");
+        assert_eq!(
+            RespNull::decode(&mut BytesMut::from("_")),
+            Err(RespError::NotComplete)
+        );
+        assert!(RespNull::decode(&mut BytesMut::from("_\r\r")).is_err());
+        assert_eq!(
+            RespFrame::decode(&mut BytesMut::from(NULL)),
+            Ok(RespNull.into())
+        );
     }
 }
@@ -0,0 +1,242 @@
+use std::fmt::{self, Display, Formatter, Write};
+
+use crate::RespFrame;
+
+// 和 redis-cli 一样显示 RespFrame，例如:
+// 1) "hello"
+// 2) (integer) 1
+// 3) 1) (nil)
+//    2) (error) ERR unknown command
+pub struct PrettyFrame<'a>(&'a RespFrame);
+
+impl RespFrame {
+    // a redis-cli style view of the frame, e.g. `println!("{}", frame.pretty())`
+    pub fn pretty(&self) -> PrettyFrame<'_> {
+        PrettyFrame(self)
+    }
+}
+
+impl Display for PrettyFrame<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        write_frame(self.0, "", &mut out)?;
+        // every line ends with '\n', the last one is left to the caller
+        out.pop();
+        f.write_str(&out)
+    }
+}
+
+// write the frame and a newline, prefix is written before every line but the first
+fn write_frame(frame: &RespFrame, prefix: &str, out: &mut String) -> fmt::Result {
+    match frame {
+        RespFrame::SimpleString(s) => out.push_str(&String::from_utf8_lossy(s)),
+        RespFrame::SimpleError(e) => {
+            write!(out, "(error) {}", String::from_utf8_lossy(e.as_ref()))?
+        }
+        RespFrame::BulkError(e) => write!(out, "(error) {}", String::from_utf8_lossy(e.as_ref()))?,
+        RespFrame::Integer(n) => write!(out, "(integer) {n}")?,
+        RespFrame::Double(d) => {
+            let s = d.to_string();
+            write!(out, "(double) {}", s.strip_prefix('+').unwrap_or(&s))?
+        }
+        RespFrame::BigNumber(n) => write!(out, "(big number) {n}")?,
+        RespFrame::Bool(b) => write!(out, "({b})")?,
+        RespFrame::Null(_) | RespFrame::NullArray(_) | RespFrame::NullBulkString(_) => {
+            out.push_str("(nil)")
+        }
+        RespFrame::BulkString(s) => write_quoted(s, out)?,
+        RespFrame::VerbatimString(s) => out.push_str(&String::from_utf8_lossy(s.data())),
+        RespFrame::Array(frames) => {
+            return write_items(frames.iter().map(|v| (v, None)), ')', "array", prefix, out)
+        }
+        RespFrame::Push(frames) => {
+            return write_items(frames.iter().map(|v| (v, None)), ')', "array", prefix, out)
+        }
+        RespFrame::Set(frames) => {
+            return write_items(frames.iter().map(|v| (v, None)), '~', "set", prefix, out)
+        }
+        RespFrame::Map(map) => {
+            return write_items(
+                map.iter().map(|(k, v)| (k, Some(v))),
+                '#',
+                "hash",
+                prefix,
+                out,
+            )
+        }
+        RespFrame::Attribute(attr) => {
+            let items = attr.attributes().iter().map(|(k, v)| (k, Some(v)));
+            write_items(items, '|', "hash", prefix, out)?;
+            out.push_str(prefix);
+            return write_frame(attr.frame(), prefix, out);
+        }
+    }
+    out.push('\n');
+    Ok(())
+}
+
+// numbered items of an aggregate, a map entry is written as `key => value`
+fn write_items<'a>(
+    items: impl ExactSizeIterator<Item = (&'a RespFrame, Option<&'a RespFrame>)>,
+    mark: char,
+    kind: &str,
+    prefix: &str,
+    out: &mut String,
+) -> fmt::Result {
+    let len = items.len();
+    if len == 0 {
+        writeln!(out, "(empty {kind})")?;
+        return Ok(());
+    }
+    let width = len.to_string().len();
+    // nested lines are aligned after "<index>) "
+    let nested = format!("{prefix}{}", " ".repeat(width + 2));
+    for (i, (item, value)) in items.enumerate() {
+        if i > 0 {
+            out.push_str(prefix);
+        }
+        write!(out, "{:>width$}{mark} ", i + 1)?;
+        write_frame(item, &nested, out)?;
+        if let Some(value) = value {
+            out.pop();
+            out.push_str(" => ");
+            // nested lines of the value are aligned after " => "
+            let line = out.rsplit('\n').next().unwrap_or_default();
+            let value_prefix = " ".repeat(line.chars().count());
+            write_frame(value, &value_prefix, out)?;
+        }
+    }
+    Ok(())
+}
+
+// 和 redis-cli 一样，转义引号、反斜杠和不可打印的字符
+fn write_quoted(s: &[u8], out: &mut String) -> fmt::Result {
+    out.push('"');
+    for &b in s {
+        match b {
+            b'\\' => out.push_str("\\\\"),
+            b'"' => out.push_str("\\\""),
+            b'\n' => out.push_str("\\n"),
+            b'\r' => out.push_str("\\r"),
+            b'\t' => out.push_str("\\t"),
+            0x07 => out.push_str("\\a"),
+            0x08 => out.push_str("\\b"),
+            0x20..=0x7e => out.push(b as char),
+            _ => write!(out, "\\x{b:02x}")?,
+        }
+    }
+    out.push('"');
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BulkString, RespArray, RespAttribute, RespMap, RespNull, RespSet, RespVerbatimString,
+        SimpleError,
+    };
+
+    #[test]
+    fn test_pretty_simple() {
+        assert_eq!(RespFrame::from("OK").pretty().to_string(), "OK");
+        assert_eq!(RespFrame::from(42).pretty().to_string(), "(integer) 42");
+        assert_eq!(RespFrame::from(1.5).pretty().to_string(), "(double) 1.5");
+        assert_eq!(RespFrame::from(true).pretty().to_string(), "(true)");
+        assert_eq!(RespFrame::from(RespNull).pretty().to_string(), "(nil)");
+        assert_eq!(
+            RespFrame::from(SimpleError::new("ERR boom"))
+                .pretty()
+                .to_string(),
+            "(error) ERR boom"
+        );
+        assert_eq!(
+            RespFrame::from(b"say \"hi\"\r\n\x00\xff")
+                .pretty()
+                .to_string(),
+            r#""say \"hi\"\r\n\x00\xff""#
+        );
+        assert_eq!(
+            RespFrame::from(RespVerbatimString::text("plain"))
+                .pretty()
+                .to_string(),
+            "plain"
+        );
+    }
+
+    #[test]
+    fn test_pretty_nested() {
+        let items: Vec<RespFrame> = (1..=10).map(RespFrame::from).collect();
+        let frame: RespFrame = RespArray::new([
+            BulkString::from("a").into(),
+            RespArray::new([1.into(), RespArray::new([]).into()]).into(),
+            RespArray::new(items).into(),
+        ])
+        .into();
+        let expected = [
+            "1) \"a\"",
+            "2) 1) (integer) 1",
+            "   2) (empty array)",
+            "3)  1) (integer) 1",
+            "    2) (integer) 2",
+            "    3) (integer) 3",
+            "    4) (integer) 4",
+            "    5) (integer) 5",
+            "    6) (integer) 6",
+            "    7) (integer) 7",
+            "    8) (integer) 8",
+            "    9) (integer) 9",
+            "   10) (integer) 10",
+        ];
+        assert_eq!(frame.pretty().to_string(), expected.join("\n"));
+    }
+
+    #[test]
+    fn test_pretty_map_and_set() {
+        let mut map = RespMap::new();
+        map.insert(
+            BulkString::from("server").into(),
+            BulkString::from("redis").into(),
+        );
+        map.insert(
+            BulkString::from("modules").into(),
+            RespSet::new([BulkString::from("m").into()]).into(),
+        );
+        let frame: RespFrame = map.clone().into();
+        assert_eq!(
+            frame.pretty().to_string(),
+            "1# \"server\" => \"redis\"\n2# \"modules\" => 1~ \"m\""
+        );
+
+        let frame = RespFrame::from(RespAttribute::new(map, 1));
+        assert_eq!(
+            frame.pretty().to_string(),
+            "1| \"server\" => \"redis\"\n2| \"modules\" => 1~ \"m\"\n(integer) 1"
+        );
+        assert_eq!(
+            RespFrame::from(RespMap::new()).pretty().to_string(),
+            "(empty hash)"
+        );
+    }
+
+    #[test]
+    fn test_pretty_map_of_arrays() {
+        let mut map = RespMap::new();
+        map.insert(
+            BulkString::from("a").into(),
+            RespArray::new([1.into(), 2.into()]).into(),
+        );
+        map.insert(
+            BulkString::from("bb").into(),
+            RespArray::new([RespArray::new([3.into(), 4.into()]).into()]).into(),
+        );
+        let frame = RespFrame::from(RespArray::new([map.into()]));
+        let expected = [
+            "1) 1# \"a\" => 1) (integer) 1",
+            "             2) (integer) 2",
+            "   2# \"bb\" => 1) 1) (integer) 3",
+            "                 2) (integer) 4",
+        ];
+        assert_eq!(frame.pretty().to_string(), expected.join("\n"));
+    }
+}
@@ -0,0 +1,125 @@
+use bytes::Bytes;
+
+use crate::{BulkString, RespArray, RespFrame, RespNullBulkString, SimpleError};
+
+// 连接使用的协议版本，和 redis 一样默认是 RESP2，客户端通过 HELLO 3 切换到 RESP3
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RespProtocol {
+    #[default]
+    Resp2 = 2,
+    Resp3 = 3,
+}
+
+impl RespProtocol {
+    pub fn from_version(version: i64) -> Option<Self> {
+        match version {
+            2 => Some(RespProtocol::Resp2),
+            3 => Some(RespProtocol::Resp3),
+            _ => None,
+        }
+    }
+
+    pub fn version(&self) -> i64 {
+        *self as i64
+    }
+}
+
+impl RespFrame {
+    // convert RESP3 only frames to the closest RESP2 frames:
+    // null -> null bulk string, map -> flat array of keys and values, set/push -> array,
+    // bool -> integer, double/big number/verbatim string -> bulk string,
+    // bulk error -> simple error, attributes are dropped
+    pub fn into_resp2(self) -> RespFrame {
+        match self {
+            RespFrame::Null(_) => RespNullBulkString.into(),
+            RespFrame::Bool(b) => RespFrame::Integer(b as i64),
+            RespFrame::Double(d) => {
+                let s = d.to_string();
+                BulkString::from(s.strip_prefix('+').unwrap_or(&s)).into()
+            }
+            RespFrame::BigNumber(n) => BulkString::from(n.to_string()).into(),
+            RespFrame::VerbatimString(s) => {
+                BulkString::new(Bytes::copy_from_slice(s.data())).into()
+            }
+            RespFrame::BulkError(e) => {
+                // simple errors can not contain CRLF
+                let msg: Vec<u8> = e
+                    .as_ref()
+                    .iter()
+                    .map(|&b| if b == b'\r' || b == b'\n' { b' ' } else { b })
+                    .collect();
+                SimpleError::new(msg).into()
+            }
+            RespFrame::Array(frames) => into_resp2_array(frames),
+            RespFrame::Set(frames) => into_resp2_array(frames),
+            RespFrame::Push(frames) => into_resp2_array(frames),
+            RespFrame::Map(map) => into_resp2_array(map.into_iter().flat_map(|(k, v)| [k, v])),
+            RespFrame::Attribute(attr) => attr.into_parts().1.into_resp2(),
+            frame => frame,
+        }
+    }
+}
+
+fn into_resp2_array(frames: impl IntoIterator<Item = RespFrame>) -> RespFrame {
+    let frames: Vec<_> = frames.into_iter().map(RespFrame::into_resp2).collect();
+    RespArray::new(frames).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BulkError, RespAttribute, RespBigNumber, RespEncode, RespMap, RespNull, RespPush, RespSet,
+        RespVerbatimString,
+    };
+
+    #[test]
+    fn test_into_resp2() {
+        assert_eq!(RespFrame::from(RespNull).into_resp2().encode(), b"$-1\r\n");
+        assert_eq!(RespFrame::from(true).into_resp2().encode(), b":+1\r\n");
+        assert_eq!(RespFrame::from(false).into_resp2().encode(), b":+0\r\n");
+        assert_eq!(RespFrame::from(1.5).into_resp2().encode(), b"$3\r\n1.5\r\n");
+        assert_eq!(
+            RespFrame::from(f64::NEG_INFINITY).into_resp2().encode(),
+            b"$4\r\n-inf\r\n"
+        );
+        assert_eq!(
+            RespFrame::from(RespBigNumber::from(-12i64))
+                .into_resp2()
+                .encode(),
+            b"$3\r\n-12\r\n"
+        );
+        assert_eq!(
+            RespFrame::from(RespVerbatimString::text("hi"))
+                .into_resp2()
+                .encode(),
+            b"$2\r\nhi\r\n"
+        );
+        assert_eq!(
+            RespFrame::from(BulkError::new("ERR a\r\nb"))
+                .into_resp2()
+                .encode(),
+            b"-ERR a  b\r\n"
+        );
+    }
+
+    #[test]
+    fn test_into_resp2_aggregates() {
+        let mut map = RespMap::new();
+        map.insert("a".into(), RespSet::new([true.into()]).into());
+        map.insert(1.into(), RespNull.into());
+        assert_eq!(
+            RespFrame::from(map.clone()).into_resp2().encode(),
+            b"*4\r\n+a\r\n*1\r\n:+1\r\n:+1\r\n$-1\r\n"
+        );
+
+        let push = RespPush::new([BulkString::from("message").into(), 1.5.into()]);
+        assert_eq!(
+            RespFrame::from(push).into_resp2().encode(),
+            b"*2\r\n$7\r\nmessage\r\n$3\r\n1.5\r\n"
+        );
+
+        let frame = RespFrame::from(RespAttribute::new(map, RespNull));
+        assert_eq!(frame.into_resp2().encode(), b"$-1\r\n");
+    }
+}
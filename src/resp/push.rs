@@ -0,0 +1,117 @@
+use std::ops::Deref;
+
+use bytes::{Buf, BufMut, BytesMut};
+
+use super::{capacity_hint, frame_length, read_len, CRLF};
+use crate::{RespDecode, RespEncode, RespFrame, RespLimits, RespResult};
+
+// push: "><number-of-elements>\r\n<element-1>...<element-n>"
+// 服务端主动推送的消息 (pub/sub, 缓存失效通知等)，第一个元素一般是消息类型
+pub(crate) const PREFIX: u8 = b'>';
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct RespPush(Vec<RespFrame>);
+
+impl RespPush {
+    pub fn new(s: impl Into<Vec<RespFrame>>) -> Self {
+        RespPush(s.into())
+    }
+}
+
+impl RespEncode for RespPush {
+    fn encode_to(&self, buf: &mut impl BufMut) {
+        buf.put_u8(PREFIX);
+        buf.put_slice(self.len().to_string().as_bytes());
+        buf.put_slice(CRLF);
+        for frame in self.iter() {
+            frame.encode_to(buf);
+        }
+    }
+
+    fn byte_size(&self) -> usize {
+        let mut size = 1 + self.len().to_string().len() + 2;
+        for frame in self.iter() {
+            size += frame.byte_size();
+        }
+        size
+    }
+}
+
+impl RespDecode for RespPush {
+    fn decode(buf: &mut BytesMut) -> RespResult<Self> {
+        Self::expect_length(buf)?;
+        Self::decode_complete(buf)
+    }
+
+    fn expect_length(buf: &[u8]) -> RespResult<usize> {
+        frame_length(buf, &RespLimits::default())
+    }
+}
+
+impl RespPush {
+    pub(crate) fn decode_complete(buf: &mut BytesMut) -> RespResult<Self> {
+        let (count, offset) = read_len(PREFIX, buf)?;
+        buf.advance(offset);
+        let mut frames = Vec::with_capacity(capacity_hint(count, buf));
+        for _ in 0..count {
+            frames.push(RespFrame::decode_complete(buf)?);
+        }
+        Ok(RespPush(frames))
+    }
+}
+
+impl IntoIterator for RespPush {
+    type Item = RespFrame;
+    type IntoIter = std::vec::IntoIter<RespFrame>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl Deref for RespPush {
+    type Target = Vec<RespFrame>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, RespArray, RespError};
+    use anyhow::Result;
+
+    #[test]
+    fn test_push_encode() {
+        let frame: RespFrame = RespPush::new([
+            BulkString::from("message").into(),
+            BulkString::from("channel").into(),
+            BulkString::from("hello").into(),
+        ])
+        .into();
+        let buf = b">3\r\n$7\r\nmessage\r\n$7\r\nchannel\r\n$5\r\nhello\r\n";
+        assert_eq!(&frame.encode(), buf);
+        assert_eq!(frame.byte_size(), buf.len());
+    }
+
+    #[test]
+    fn test_push_decode() -> Result<()> {
+        let mut buf = BytesMut::from(">2\r\n$10\r\ninvalidate\r\n*1\r\n$3\r\nfoo\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(
+            frame,
+            RespPush::new([
+                BulkString::from("invalidate").into(),
+                RespArray::new([BulkString::from("foo").into()]).into(),
+            ])
+            .into()
+        );
+        assert!(buf.is_empty());
+
+        let mut buf = BytesMut::from(">2\r\n$10\r\ninvalidate\r\n");
+        assert_eq!(RespPush::decode(&mut buf), Err(RespError::NotComplete));
+        Ok(())
+    }
+}
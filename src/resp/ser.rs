@@ -0,0 +1,434 @@
+use serde::{ser, Serialize};
+use std::fmt::Display;
+
+use crate::{BulkString, RespArray, RespBigNumber, RespError, RespFrame, RespMap, RespNull};
+
+// 把 rust 类型映射成 RespFrame:
+// struct/map -> RespMap, seq/tuple -> RespArray, None/() -> RespNull,
+// 整数 -> Integer（超出 i64 时用 BigNumber）, 浮点数 -> Double, 字符串/bytes -> BulkString,
+// enum 的 unit variant -> BulkString, 其余 variant -> {variant: value}
+pub fn to_frame<T: Serialize + ?Sized>(value: &T) -> Result<RespFrame, RespError> {
+    value.serialize(Serializer)
+}
+
+impl ser::Error for RespError {
+    fn custom<T: Display>(msg: T) -> Self {
+        RespError::Message(msg.to_string())
+    }
+}
+
+struct Serializer;
+
+// {variant: value}
+fn variant_frame(variant: &'static str, value: RespFrame) -> RespFrame {
+    let mut map = RespMap::new();
+    map.insert(BulkString::from(variant).into(), value);
+    map.into()
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = RespFrame;
+    type Error = RespError;
+
+    type SerializeSeq = SerializeVec;
+    type SerializeTuple = SerializeVec;
+    type SerializeTupleStruct = SerializeVec;
+    type SerializeTupleVariant = SerializeVec;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeMap;
+
+    fn serialize_bool(self, v: bool) -> Result<RespFrame, RespError> {
+        Ok(v.into())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<RespFrame, RespError> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<RespFrame, RespError> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<RespFrame, RespError> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<RespFrame, RespError> {
+        Ok(v.into())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<RespFrame, RespError> {
+        match i64::try_from(v) {
+            Ok(v) => Ok(v.into()),
+            Err(_) => Ok(RespBigNumber::from(v).into()),
+        }
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<RespFrame, RespError> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<RespFrame, RespError> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<RespFrame, RespError> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<RespFrame, RespError> {
+        self.serialize_i128(v as i128)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<RespFrame, RespError> {
+        match i128::try_from(v) {
+            Ok(v) => self.serialize_i128(v),
+            Err(_) => Ok(RespBigNumber::try_from(v.to_string().as_str())?.into()),
+        }
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<RespFrame, RespError> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<RespFrame, RespError> {
+        Ok(v.into())
+    }
+
+    fn serialize_char(self, v: char) -> Result<RespFrame, RespError> {
+        Ok(BulkString::from(v.to_string()).into())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<RespFrame, RespError> {
+        Ok(BulkString::from(v).into())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<RespFrame, RespError> {
+        Ok(BulkString::from(v).into())
+    }
+
+    fn serialize_none(self) -> Result<RespFrame, RespError> {
+        Ok(RespNull.into())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<RespFrame, RespError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<RespFrame, RespError> {
+        Ok(RespNull.into())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<RespFrame, RespError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<RespFrame, RespError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<RespFrame, RespError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<RespFrame, RespError> {
+        Ok(variant_frame(variant, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeVec, RespError> {
+        Ok(SerializeVec {
+            vec: Vec::with_capacity(len.unwrap_or(0)),
+            variant: None,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeVec, RespError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeVec, RespError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVec, RespError> {
+        Ok(SerializeVec {
+            vec: Vec::with_capacity(len),
+            variant: Some(variant),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeMap, RespError> {
+        Ok(SerializeMap {
+            map: RespMap::new(),
+            key: None,
+            variant: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeMap, RespError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeMap, RespError> {
+        Ok(SerializeMap {
+            map: RespMap::new(),
+            key: None,
+            variant: Some(variant),
+        })
+    }
+}
+
+struct SerializeVec {
+    vec: Vec<RespFrame>,
+    // set for tuple variants
+    variant: Option<&'static str>,
+}
+
+impl SerializeVec {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RespError> {
+        self.vec.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<RespFrame, RespError> {
+        let frame = RespArray::new(self.vec).into();
+        match self.variant {
+            Some(variant) => Ok(variant_frame(variant, frame)),
+            None => Ok(frame),
+        }
+    }
+}
+
+impl ser::SerializeSeq for SerializeVec {
+    type Ok = RespFrame;
+    type Error = RespError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RespError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RespFrame, RespError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SerializeVec {
+    type Ok = RespFrame;
+    type Error = RespError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RespError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RespFrame, RespError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeVec {
+    type Ok = RespFrame;
+    type Error = RespError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RespError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RespFrame, RespError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeVec {
+    type Ok = RespFrame;
+    type Error = RespError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RespError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RespFrame, RespError> {
+        self.finish()
+    }
+}
+
+struct SerializeMap {
+    map: RespMap,
+    // key waiting for its value
+    key: Option<RespFrame>,
+    // set for struct variants
+    variant: Option<&'static str>,
+}
+
+impl SerializeMap {
+    fn insert<T: Serialize + ?Sized>(
+        &mut self,
+        key: RespFrame,
+        value: &T,
+    ) -> Result<(), RespError> {
+        self.map.insert(key, value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<RespFrame, RespError> {
+        let frame = self.map.into();
+        match self.variant {
+            Some(variant) => Ok(variant_frame(variant, frame)),
+            None => Ok(frame),
+        }
+    }
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = RespFrame;
+    type Error = RespError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), RespError> {
+        self.key = Some(key.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RespError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| RespError::Message("map value without a key".to_string()))?;
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<RespFrame, RespError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = RespFrame;
+    type Error = RespError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), RespError> {
+        self.insert(BulkString::from(key).into(), value)
+    }
+
+    fn end(self) -> Result<RespFrame, RespError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeMap {
+    type Ok = RespFrame;
+    type Error = RespError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), RespError> {
+        self.insert(BulkString::from(key).into(), value)
+    }
+
+    fn end(self) -> Result<RespFrame, RespError> {
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RespEncode;
+    use serde::Serialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    struct User {
+        name: String,
+        age: u8,
+        score: f64,
+        email: Option<String>,
+        tags: Vec<&'static str>,
+    }
+
+    #[derive(Serialize)]
+    enum Event {
+        Login,
+        Visit(String),
+        Move { x: i32, y: i32 },
+    }
+
+    #[test]
+    fn test_to_frame_struct() -> Result<(), RespError> {
+        let user = User {
+            name: "alice".to_string(),
+            age: 30,
+            score: 1.5,
+            email: None,
+            tags: vec!["a", "b"],
+        };
+        let frame = to_frame(&user)?;
+        assert_eq!(
+            frame.encode(),
+            b"%5\r\n$4\r\nname\r\n$5\r\nalice\r\n$3\r\nage\r\n:+30\r\n$5\r\nscore\r\n,+1.5\r\n$5\r\nemail\r\n_\r\n$4\r\ntags\r\n*2\r\n$1\r\na\r\n$1\r\nb\r\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_frame_values() -> Result<(), RespError> {
+        assert_eq!(to_frame(&u64::MAX)?.encode(), b"(18446744073709551615\r\n");
+        assert_eq!(to_frame(&-1i8)?, RespFrame::Integer(-1));
+        assert_eq!(to_frame(&())?, RespNull.into());
+        assert_eq!(to_frame(&(1, "a"))?.encode(), b"*2\r\n:+1\r\n$1\r\na\r\n");
+
+        let map = BTreeMap::from([(1, true)]);
+        assert_eq!(to_frame(&map)?.encode(), b"%1\r\n:+1\r\n#t\r\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_frame_enum() -> Result<(), RespError> {
+        assert_eq!(to_frame(&Event::Login)?.encode(), b"$5\r\nLogin\r\n");
+        assert_eq!(
+            to_frame(&Event::Visit("/".to_string()))?.encode(),
+            b"%1\r\n$5\r\nVisit\r\n$1\r\n/\r\n"
+        );
+        assert_eq!(
+            to_frame(&Event::Move { x: 1, y: -1 })?.encode(),
+            b"%1\r\n$4\r\nMove\r\n%2\r\n$1\r\nx\r\n:+1\r\n$1\r\ny\r\n:-1\r\n"
+        );
+        Ok(())
+    }
+}
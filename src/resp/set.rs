@@ -1,34 +1,61 @@
-use super::read_len;
-use crate::{RespDecode, RespEncode, RespFrame, RespResult};
-use std::ops::{Deref, DerefMut};
+use super::{capacity_hint, frame_length, read_len, stream};
+use crate::{RespDecode, RespEncode, RespFrame, RespLimits, RespResult};
+use bytes::{Buf, BufMut, BytesMut};
+use indexmap::IndexSet;
+use std::{
+    cmp::Ordering,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    ops::{Deref, DerefMut},
+};
 
 pub(crate) const PREFIX: u8 = b'~';
 
 // set: "~<number-of-elements>\r\n<element-1>...<element-n>"
-// 目前使用Vec实现，不使用HashSet是因为目前RespFrame不能直接实现Eq和Hash
-#[derive(Debug, Clone, PartialEq)]
-pub struct RespSet(Vec<RespFrame>);
+// 重复元素只保留第一个，按插入顺序编码，保证输出稳定。
+// 比较时和顺序无关
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RespSet(IndexSet<RespFrame>);
 
 impl RespSet {
-    pub fn new(s: impl Into<Vec<RespFrame>>) -> Self {
-        RespSet(s.into())
+    pub fn new(s: impl IntoIterator<Item = RespFrame>) -> Self {
+        RespSet(s.into_iter().collect())
+    }
+
+    // elements in self or other, elements of self come first
+    pub fn union(&self, other: &RespSet) -> RespSet {
+        RespSet(self.0.union(&other.0).cloned().collect())
+    }
+
+    // elements in both self and other, in the order of self
+    pub fn intersection(&self, other: &RespSet) -> RespSet {
+        RespSet(self.0.intersection(&other.0).cloned().collect())
+    }
+
+    // elements in self but not in other, in the order of self
+    pub fn difference(&self, other: &RespSet) -> RespSet {
+        RespSet(self.0.difference(&other.0).cloned().collect())
+    }
+
+    fn sorted(&self) -> Vec<&RespFrame> {
+        let mut frames: Vec<_> = self.iter().collect();
+        frames.sort();
+        frames
     }
 }
 
 impl RespEncode for RespSet {
-    fn encode(&self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(self.byte_size());
-        buf.push(PREFIX);
-        buf.extend_from_slice(self.len().to_string().as_bytes());
-        buf.extend_from_slice(b"\r\n");
+    fn encode_to(&self, buf: &mut impl BufMut) {
+        buf.put_u8(PREFIX);
+        buf.put_slice(self.len().to_string().as_bytes());
+        buf.put_slice(b"\r\n");
         for frame in self.iter() {
-            buf.extend_from_slice(&frame.encode());
+            frame.encode_to(buf);
         }
-        buf
     }
 
     fn byte_size(&self) -> usize {
-        //%<number-of-entries>\r\n
+        //~<number-of-elements>\r\n
         let mut size = self.len().to_string().len() + 3;
         for v in self.iter() {
             size += v.byte_size();
@@ -38,20 +65,74 @@ impl RespEncode for RespSet {
 }
 
 impl RespDecode for RespSet {
-    fn decode(_buf: &[u8]) -> RespResult<Self> {
-        let (num, mut offset) = read_len(PREFIX, _buf)?;
-        let mut frames = Vec::with_capacity(num as usize);
+    fn decode(buf: &mut BytesMut) -> RespResult<Self> {
+        Self::expect_length(buf)?;
+        Self::decode_complete(buf)
+    }
+
+    fn expect_length(buf: &[u8]) -> RespResult<usize> {
+        frame_length(buf, &RespLimits::default())
+    }
+}
+
+impl RespSet {
+    pub(crate) fn decode_complete(buf: &mut BytesMut) -> RespResult<Self> {
+        if stream::is_streamed(buf) {
+            return stream::decode_elements(buf).map(RespSet::new);
+        }
+        let (num, offset) = read_len(PREFIX, buf)?;
+        buf.advance(offset);
+        let mut frames = IndexSet::with_capacity(capacity_hint(num, buf));
         for _ in 0..num {
-            let frame = RespFrame::decode(&_buf[offset..])?;
-            offset += frame.byte_size();
-            frames.push(frame);
+            frames.insert(RespFrame::decode_complete(buf)?);
         }
         Ok(RespSet(frames))
     }
 }
 
+impl Hash for RespSet {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // 与顺序无关：每个元素单独哈希后相加
+        let mut sum: u64 = 0;
+        for frame in self.iter() {
+            let mut hasher = DefaultHasher::new();
+            frame.hash(&mut hasher);
+            sum = sum.wrapping_add(hasher.finish());
+        }
+        self.len().hash(state);
+        sum.hash(state);
+    }
+}
+
+impl PartialOrd for RespSet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RespSet {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sorted().cmp(&other.sorted())
+    }
+}
+
+impl<T: Into<RespFrame>> FromIterator<T> for RespSet {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        RespSet(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+impl IntoIterator for RespSet {
+    type Item = RespFrame;
+    type IntoIter = indexmap::set::IntoIter<RespFrame>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
 impl Deref for RespSet {
-    type Target = Vec<RespFrame>;
+    type Target = IndexSet<RespFrame>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -85,17 +166,53 @@ mod tests {
 
     #[test]
     fn test_set_decode() -> Result<()> {
-        let buf = b"~2\r\n$3\r\nset\r\n$5\r\nhello\r\n";
+        let mut buf = BytesMut::from("~2\r\n$3\r\nset\r\n$5\r\nhello\r\n");
 
-        let frame = RespSet::decode(buf)?;
+        let frame = RespSet::decode(&mut buf)?;
         assert_eq!(
             frame,
             RespSet::new(vec![
-                BulkString::new(b"set".to_vec()).into(),
-                BulkString::new(b"hello".to_vec()).into()
+                BulkString::from("set").into(),
+                BulkString::from("hello").into()
             ])
         );
 
         Ok(())
     }
+
+    #[test]
+    fn test_set_dedup() -> Result<()> {
+        let mut buf = BytesMut::from("~3\r\n+a\r\n+b\r\n+a\r\n");
+        let set = RespSet::decode(&mut buf)?;
+        assert_eq!(set.len(), 2);
+        assert!(buf.is_empty());
+        assert!(set.contains(&RespFrame::from("a")));
+        assert!(!set.contains(&RespFrame::from(b"a")));
+        assert_eq!(set.encode(), b"~2\r\n+a\r\n+b\r\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_compare() {
+        let a: RespSet = ["a", "b"].into_iter().collect();
+        let b: RespSet = ["b", "a"].into_iter().collect();
+        assert_eq!(a, b);
+        assert_eq!(a.cmp(&b), Ordering::Equal);
+
+        let hash = |s: &RespSet| {
+            let mut hasher = DefaultHasher::new();
+            s.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&a), hash(&b));
+    }
+
+    #[test]
+    fn test_set_ops() {
+        let a: RespSet = [1, 2, 3].into_iter().collect();
+        let b: RespSet = [4, 3, 2].into_iter().collect();
+        assert_eq!(a.union(&b).encode(), b"~4\r\n:+1\r\n:+2\r\n:+3\r\n:+4\r\n");
+        assert_eq!(a.intersection(&b), [2, 3].into_iter().collect());
+        assert_eq!(a.difference(&b), [1].into_iter().collect());
+    }
 }
@@ -1,27 +1,25 @@
-use std::str::from_utf8;
+use bytes::{BufMut, Bytes, BytesMut};
 
-use super::{find_crlf, CRLF};
+use super::{line_length, CRLF};
 use crate::{RespDecode, RespEncode, RespResult};
 
 // error: "-Error message\r\n"
 pub(crate) const PREFIX: u8 = b'-';
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct SimpleError(String);
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SimpleError(Bytes);
 
 impl SimpleError {
-    pub fn new(s: impl Into<String>) -> Self {
+    pub fn new(s: impl Into<Bytes>) -> Self {
         Self(s.into())
     }
 }
 
 impl RespEncode for SimpleError {
-    fn encode(&self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(self.byte_size());
-        buf.push(PREFIX);
-        buf.extend_from_slice(self.0.as_bytes());
-        buf.extend_from_slice(CRLF);
-        buf
+    fn encode_to(&self, buf: &mut impl BufMut) {
+        buf.put_u8(PREFIX);
+        buf.put_slice(&self.0);
+        buf.put_slice(CRLF);
     }
 
     fn byte_size(&self) -> usize {
@@ -30,9 +28,20 @@ impl RespEncode for SimpleError {
 }
 
 impl RespDecode for SimpleError {
-    fn decode(buf: &[u8]) -> RespResult<Self> {
-        let end = find_crlf(buf).ok_or(super::RespError::NotComplete)?;
-        Ok(Self::new(from_utf8(&buf[1..end])?))
+    fn decode(buf: &mut BytesMut) -> RespResult<Self> {
+        let len = Self::expect_length(buf)?;
+        let data = buf.split_to(len).freeze();
+        Ok(Self(data.slice(1..len - CRLF.len())))
+    }
+
+    fn expect_length(buf: &[u8]) -> RespResult<usize> {
+        line_length(buf)
+    }
+}
+
+impl AsRef<[u8]> for SimpleError {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
     }
 }
 
@@ -62,11 +71,11 @@ mod tests {
     #[test]
     fn test_decode() {
         assert_eq!(
-            RespFrame::decode(b"-Error message\r\n"),
+            RespFrame::decode(&mut BytesMut::from("-Error message\r\n")),
             Ok(SimpleError::new("Error message").into()),
         );
         assert_eq!(
-            RespFrame::decode(b"-Error message"),
+            RespFrame::decode(&mut BytesMut::from("-Error message")),
             Err(RespError::NotComplete),
         );
     }
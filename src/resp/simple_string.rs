@@ -1,31 +1,32 @@
 use std::{
+    fmt::{self, Display, Formatter},
     ops::Deref,
     str::{from_utf8, FromStr},
 };
 
-use crate::{RespDecode, RespEncode, RespError, RespResult};
+use bytes::{BufMut, Bytes, BytesMut};
 
-use super::{find_crlf, CRLF};
+use crate::{RespDecode, RespEncode, RespResult};
+
+use super::{line_length, CRLF};
 
 // simple string: "+OK\r\n"
 pub(crate) const PREFIX: u8 = b'+';
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct SimpleString(pub(crate) String);
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SimpleString(pub(crate) Bytes);
 
 impl SimpleString {
     pub fn new(s: impl Into<String>) -> Self {
-        Self(s.into())
+        Self(Bytes::from(s.into()))
     }
 }
 
 impl RespEncode for SimpleString {
-    fn encode(&self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(self.byte_size());
-        buf.push(PREFIX);
-        buf.extend_from_slice(self.0.as_bytes());
-        buf.extend_from_slice(CRLF);
-        buf
+    fn encode_to(&self, buf: &mut impl BufMut) {
+        buf.put_u8(PREFIX);
+        buf.put_slice(&self.0);
+        buf.put_slice(CRLF);
     }
 
     fn byte_size(&self) -> usize {
@@ -34,10 +35,16 @@ impl RespEncode for SimpleString {
 }
 
 impl RespDecode for SimpleString {
-    fn decode(buf: &[u8]) -> RespResult<Self> {
-        // 认为都从 RespFrame::decode 调用，不检查长度和前缀
-        let end = find_crlf(buf).ok_or(RespError::NotComplete)?;
-        Ok(Self::new(from_utf8(&buf[1..end])?))
+    fn decode(buf: &mut BytesMut) -> RespResult<Self> {
+        // 认为都从 RespFrame::decode 调用，不检查前缀
+        let len = Self::expect_length(buf)?;
+        from_utf8(&buf[1..len - CRLF.len()])?;
+        let data = buf.split_to(len).freeze();
+        Ok(Self(data.slice(1..len - CRLF.len())))
+    }
+
+    fn expect_length(buf: &[u8]) -> RespResult<usize> {
+        line_length(buf)
     }
 }
 
@@ -51,7 +58,7 @@ impl FromStr for SimpleString {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self(s.to_string()))
+        Ok(Self::new(s))
     }
 }
 
@@ -61,8 +68,14 @@ impl From<String> for SimpleString {
     }
 }
 
+impl Display for SimpleString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", String::from_utf8_lossy(&self.0))
+    }
+}
+
 impl Deref for SimpleString {
-    type Target = str;
+    type Target = [u8];
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -93,14 +106,20 @@ mod tests {
 
     #[test]
     fn test_decode() {
-        assert_eq!(SimpleString::decode(SIMPLE_OK), Ok(SimpleString::new("OK")));
-        assert_eq!(
-            SimpleString::decode(b"+OK\r\nextra"),
-            Ok(SimpleString::new("OK"))
-        );
-        assert_eq!(SimpleString::decode(b"+OK\r"), Err(RespError::NotComplete));
+        let mut buf = BytesMut::from(SIMPLE_OK);
+        assert_eq!(SimpleString::decode(&mut buf), Ok(SimpleString::new("OK")));
+        assert!(buf.is_empty());
+
+        let mut buf = BytesMut::from("+OK\r\nextra");
+        assert_eq!(SimpleString::decode(&mut buf), Ok(SimpleString::new("OK")));
+        assert_eq!(buf, "extra");
+
+        let mut buf = BytesMut::from("+OK\r");
+        assert_eq!(SimpleString::decode(&mut buf), Err(RespError::NotComplete));
+        assert_eq!(buf, "+OK\r");
+
         assert_eq!(
-            RespFrame::decode(SIMPLE_OK),
+            RespFrame::decode(&mut BytesMut::from(SIMPLE_OK)),
             Ok(SimpleString::new("OK").into())
         );
     }
@@ -0,0 +1,189 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use super::{array, bulk_string, map, read_len, set, CRLF};
+use crate::{RespEncode, RespError, RespFrame, RespResult};
+
+// streamed string: "$?\r\n;<length>\r\n<data>\r\n...;0\r\n"
+// streamed aggregate: "*?\r\n<element-1>...<element-n>.\r\n", also "%?" and "~?"
+pub(crate) const CHUNK_PREFIX: u8 = b';';
+pub(crate) const END_PREFIX: u8 = b'.';
+const END: &[u8] = b".\r\n";
+const CHUNK_END: &[u8] = b";0\r\n";
+
+// 分段发送大的回复时使用：先发送 *Start，再发送 Chunk 或者普通的帧，最后发送 End。
+// 接收方解码后得到普通的 BulkString/RespArray/RespMap/RespSet
+#[derive(Debug, Clone, PartialEq)]
+pub enum RespStreamPart {
+    // "$?\r\n"
+    StringStart,
+    // ";<length>\r\n<data>\r\n", an empty chunk writes nothing as ";0" ends the string
+    Chunk(Bytes),
+    // ";0\r\n"
+    StringEnd,
+    // "*?\r\n"
+    ArrayStart,
+    // "%?\r\n"
+    MapStart,
+    // "~?\r\n"
+    SetStart,
+    // ".\r\n"
+    End,
+}
+
+impl RespStreamPart {
+    fn start(prefix: u8, buf: &mut impl BufMut) {
+        buf.put_u8(prefix);
+        buf.put_u8(b'?');
+        buf.put_slice(CRLF);
+    }
+}
+
+impl RespEncode for RespStreamPart {
+    fn encode_to(&self, buf: &mut impl BufMut) {
+        match self {
+            RespStreamPart::StringStart => Self::start(bulk_string::PREFIX, buf),
+            RespStreamPart::Chunk(data) if data.is_empty() => {}
+            RespStreamPart::Chunk(data) => {
+                buf.put_u8(CHUNK_PREFIX);
+                buf.put_slice(data.len().to_string().as_bytes());
+                buf.put_slice(CRLF);
+                buf.put_slice(data);
+                buf.put_slice(CRLF);
+            }
+            RespStreamPart::StringEnd => buf.put_slice(CHUNK_END),
+            RespStreamPart::ArrayStart => Self::start(array::PREFIX, buf),
+            RespStreamPart::MapStart => Self::start(map::PREFIX, buf),
+            RespStreamPart::SetStart => Self::start(set::PREFIX, buf),
+            RespStreamPart::End => buf.put_slice(END),
+        }
+    }
+
+    fn byte_size(&self) -> usize {
+        match self {
+            RespStreamPart::Chunk(data) if data.is_empty() => 0,
+            RespStreamPart::Chunk(data) => data.len() + data.len().to_string().len() + 5,
+            RespStreamPart::StringEnd => CHUNK_END.len(),
+            RespStreamPart::End => END.len(),
+            _ => 4,
+        }
+    }
+}
+
+// whether the frame at the front of buf is streamed, e.g. "$?\r\n"
+pub(crate) fn is_streamed(buf: &[u8]) -> bool {
+    buf.get(1) == Some(&b'?')
+}
+
+// decode the chunks of a complete streamed string into one buffer
+pub(crate) fn decode_chunks(buf: &mut BytesMut) -> RespResult<Bytes> {
+    // "$?\r\n"
+    buf.advance(4);
+    let mut data = BytesMut::new();
+    loop {
+        let (len, offset) = read_len(CHUNK_PREFIX, buf)?;
+        buf.advance(offset);
+        if len == 0 {
+            return Ok(data.freeze());
+        }
+        if &buf[len..len + CRLF.len()] != CRLF {
+            return Err(RespError::InvalidFrame(format!(
+                "Invalid chunk tail: {:?}",
+                &buf[len..len + CRLF.len()]
+            )));
+        }
+        data.extend_from_slice(&buf[..len]);
+        buf.advance(len + CRLF.len());
+    }
+}
+
+// decode the elements of a complete streamed aggregate
+pub(crate) fn decode_elements(buf: &mut BytesMut) -> RespResult<Vec<RespFrame>> {
+    // "*?\r\n"
+    buf.advance(4);
+    let mut frames = Vec::new();
+    while !buf.starts_with(END) {
+        frames.push(RespFrame::decode_complete(buf)?);
+    }
+    buf.advance(END.len());
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, RespArray, RespDecode, RespMap, RespSet};
+    use anyhow::Result;
+
+    #[test]
+    fn test_stream_part_encode() {
+        let parts = [
+            RespStreamPart::StringStart,
+            RespStreamPart::Chunk("Hell".into()),
+            RespStreamPart::Chunk(Bytes::new()),
+            RespStreamPart::Chunk("o world".into()),
+            RespStreamPart::StringEnd,
+        ];
+        let mut buf = Vec::new();
+        for part in parts.iter() {
+            part.encode_to(&mut buf);
+            assert_eq!(part.encode().len(), part.byte_size());
+        }
+        assert_eq!(buf, b"$?\r\n;4\r\nHell\r\n;7\r\no world\r\n;0\r\n");
+        assert_eq!(RespStreamPart::MapStart.encode(), b"%?\r\n");
+        assert_eq!(RespStreamPart::End.encode(), b".\r\n");
+    }
+
+    #[test]
+    fn test_streamed_string_decode() -> Result<()> {
+        let mut buf = BytesMut::from("$?\r\n;4\r\nHell\r\n;5\r\no wor\r\n;1\r\nd\r\n;0\r\n+OK\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(frame, BulkString::from("Hello word").into());
+        assert_eq!(buf, "+OK\r\n");
+
+        let mut buf = BytesMut::from("$?\r\n;4\r\nHell\r\n");
+        assert_eq!(RespFrame::decode(&mut buf), Err(RespError::NotComplete));
+        let frame = BulkString::decode(&mut BytesMut::from("$?\r\n;0\r\n"))?;
+        assert_eq!(frame, BulkString::default());
+
+        assert!(RespFrame::decode(&mut BytesMut::from("$?\r\n:1\r\n;0\r\n")).is_err());
+        assert!(RespFrame::decode(&mut BytesMut::from(";1\r\na\r\n")).is_err());
+        // 每个 chunk 后面必须是 CRLF
+        assert_eq!(
+            RespFrame::decode(&mut BytesMut::from("$?\r\n;1\r\naXX;0\r\n")),
+            Err(RespError::InvalidFrame(
+                "Invalid chunk tail: [88, 88]".to_string()
+            ))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_streamed_aggregate_decode() -> Result<()> {
+        let mut buf = BytesMut::from("*?\r\n:+1\r\n*?\r\n$?\r\n;1\r\na\r\n;0\r\n.\r\n.\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert_eq!(
+            frame,
+            RespArray::new([
+                1.into(),
+                RespArray::new([BulkString::from("a").into()]).into()
+            ])
+        );
+        assert!(buf.is_empty());
+
+        let frame =
+            RespFrame::decode(&mut BytesMut::from("%?\r\n+a\r\n:+1\r\n+b\r\n:+2\r\n.\r\n"))?;
+        let map: RespMap = [("a", 1), ("b", 2)].into_iter().collect();
+        assert_eq!(frame, map.into());
+
+        let frame = RespFrame::decode(&mut BytesMut::from("~?\r\n:+1\r\n:+1\r\n.\r\n"))?;
+        assert_eq!(frame, RespSet::new([1.into()]).into());
+
+        assert_eq!(
+            RespFrame::decode(&mut BytesMut::from("*?\r\n:+1\r\n")),
+            Err(RespError::NotComplete)
+        );
+        assert!(RespFrame::decode(&mut BytesMut::from("%?\r\n+a\r\n.\r\n")).is_err());
+        assert!(RespFrame::decode(&mut BytesMut::from("*1\r\n.\r\n")).is_err());
+        Ok(())
+    }
+}
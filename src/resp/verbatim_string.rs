@@ -0,0 +1,144 @@
+use std::fmt::{self, Display, Formatter};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use super::{read_len, CRLF};
+use crate::{RespDecode, RespEncode, RespError, RespResult};
+
+// verbatim string: "=<length>\r\n<format>:<data>\r\n"
+pub(crate) const PREFIX: u8 = b'=';
+// "<format>:" 的长度
+const FORMAT_LEN: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RespVerbatimString {
+    format: [u8; 3],
+    data: Bytes,
+}
+
+impl RespVerbatimString {
+    pub fn new(format: [u8; 3], data: impl Into<Bytes>) -> Self {
+        Self {
+            format,
+            data: data.into(),
+        }
+    }
+
+    // plain text, "txt"
+    pub fn text(data: impl Into<Bytes>) -> Self {
+        Self::new(*b"txt", data)
+    }
+
+    // markdown, "mkd"
+    pub fn markdown(data: impl Into<Bytes>) -> Self {
+        Self::new(*b"mkd", data)
+    }
+
+    pub fn format(&self) -> &[u8; 3] {
+        &self.format
+    }
+
+    pub fn data(&self) -> &Bytes {
+        &self.data
+    }
+}
+
+impl RespEncode for RespVerbatimString {
+    fn encode_to(&self, buf: &mut impl BufMut) {
+        buf.put_u8(PREFIX);
+        buf.put_slice((self.data.len() + FORMAT_LEN).to_string().as_bytes());
+        buf.put_slice(CRLF);
+        buf.put_slice(&self.format);
+        buf.put_u8(b':');
+        buf.put_slice(&self.data);
+        buf.put_slice(CRLF);
+    }
+
+    fn byte_size(&self) -> usize {
+        let len = self.data.len() + FORMAT_LEN;
+        len + len.to_string().len() + 5
+    }
+}
+
+impl RespDecode for RespVerbatimString {
+    fn decode(buf: &mut BytesMut) -> RespResult<Self> {
+        let (len, offset) = read_len(PREFIX, buf)?;
+        let total_len = Self::expect_length(buf)?;
+        if len < FORMAT_LEN || buf[offset + FORMAT_LEN - 1] != b':' {
+            return Err(RespError::InvalidFrame(format!(
+                "Invalid verbatim string format: {:?}",
+                &buf[offset..offset + len.min(FORMAT_LEN)]
+            )));
+        }
+        if buf[total_len - 2] != CRLF[0] || buf[total_len - 1] != CRLF[1] {
+            return Err(RespError::InvalidFrame(format!(
+                "Invalid verbatim string tail: {:?}",
+                &buf[total_len - 2..total_len]
+            )));
+        }
+        let mut data = buf.split_to(total_len);
+        data.advance(offset);
+        let format = [data[0], data[1], data[2]];
+        data.advance(FORMAT_LEN);
+        data.truncate(len - FORMAT_LEN);
+        Ok(Self {
+            format,
+            data: data.freeze(),
+        })
+    }
+
+    fn expect_length(buf: &[u8]) -> RespResult<usize> {
+        let (len, offset) = read_len(PREFIX, buf)?;
+        let total_len = len + offset + 2;
+        if buf.len() < total_len {
+            return Err(RespError::NotComplete);
+        }
+        Ok(total_len)
+    }
+}
+
+impl Display for RespVerbatimString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", String::from_utf8_lossy(&self.data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RespFrame;
+
+    #[test]
+    fn test_encode() {
+        let frame: RespFrame = RespVerbatimString::text("Some string").into();
+        assert_eq!(frame.encode(), b"=15\r\ntxt:Some string\r\n");
+        assert_eq!(frame.byte_size(), 22);
+
+        let frame: RespFrame = RespVerbatimString::markdown("").into();
+        assert_eq!(frame.encode(), b"=4\r\nmkd:\r\n");
+        assert_eq!(frame.byte_size(), 10);
+    }
+
+    #[test]
+    fn test_decode() {
+        let mut buf = BytesMut::from("=15\r\ntxt:Some string\r\nextra");
+        assert_eq!(
+            RespFrame::decode(&mut buf),
+            Ok(RespVerbatimString::text("Some string").into())
+        );
+        assert_eq!(buf, "extra");
+
+        let mut buf = BytesMut::from("=15\r\ntxt:Some str");
+        assert_eq!(RespFrame::decode(&mut buf), Err(RespError::NotComplete));
+
+        let mut buf = BytesMut::from("=2\r\ntx\r\n");
+        assert!(RespFrame::decode(&mut buf).is_err());
+
+        let mut buf = BytesMut::from("=15\r\ntxt Some string\r\n");
+        assert!(RespFrame::decode(&mut buf).is_err());
+
+        let v = RespVerbatimString::decode(&mut BytesMut::from("=7\r\nmkd:# a\r\n")).unwrap();
+        assert_eq!(v.format(), b"mkd");
+        assert_eq!(v.data().as_ref(), b"# a");
+    }
+}